@@ -1,7 +1,13 @@
 use spatial_codegen::AST;
 
-fn main() -> () {
-    let schema = AST::from("../schema");
+fn main() {
+    let schema = match AST::load("../schema") {
+        Ok(schema) => schema,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
     println!("{:#?}", schema);
     let result = schema.generate("../test/src/generated");
     println!("{:#?}", result);
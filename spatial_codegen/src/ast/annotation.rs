@@ -0,0 +1,92 @@
+use crate::ast::ident;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum AnnotationValue {
+    Bool(bool),
+    /// A numeric literal, kept as written.
+    Number(String),
+    String(String),
+    List(Vec<AnnotationValue>),
+    Type(Annotation),
+}
+
+/// An instance of a schema type attached to a definition, as in
+/// `[Deprecated]` or `[Range(min = 0, max = 5)]`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Annotation {
+    pub name: String,
+    pub fields: Vec<(String, AnnotationValue)>,
+}
+
+impl Annotation {
+    pub fn get_field(&self, name: &str) -> Option<&AnnotationValue> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        self.name == "Deprecated" || self.name.ends_with(".Deprecated")
+    }
+
+    fn generate_deprecated(&self) -> TokenStream {
+        let arguments = ["since", "note"]
+            .iter()
+            .filter_map(|name| match self.get_field(name) {
+                Some(AnnotationValue::String(value)) => {
+                    let name = ident(name);
+                    Some(quote! { #name = #value })
+                }
+                _ => None,
+            })
+            .collect::<Vec<TokenStream>>();
+        if arguments.is_empty() {
+            quote! { #[deprecated] }
+        } else {
+            quote! { #[deprecated(#(#arguments),*)] }
+        }
+    }
+
+    /// Returns the Rust attribute a built-in annotation stands for.
+    pub fn generate_one(&self) -> Option<TokenStream> {
+        if self.is_deprecated() {
+            Some(self.generate_deprecated())
+        } else {
+            None
+        }
+    }
+
+    pub fn generate_multiple(data: &[Self]) -> TokenStream {
+        data.iter().filter_map(Self::generate_one).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_generate_multiple() {
+        let annotations = vec![
+            Annotation {
+                name: "Range".to_string(),
+                fields: vec![("max".to_string(), AnnotationValue::Number("5".to_string()))],
+            },
+            Annotation {
+                name: "improbable.Deprecated".to_string(),
+                fields: vec![(
+                    "note".to_string(),
+                    AnnotationValue::String("use `Mass`".to_string()),
+                )],
+            },
+        ];
+        assert_eq!(
+            Annotation::generate_multiple(&annotations).to_string(),
+            quote! { #[deprecated(note = "use `Mass`")] }.to_string()
+        );
+    }
+}
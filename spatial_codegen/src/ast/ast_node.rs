@@ -1,13 +1,36 @@
+use crate::ast::ident;
+use crate::ast::pretty_print;
+use crate::ast::rust_ident;
+use crate::ast::ExportStrategy;
+use crate::ast::ModuleMap;
 use crate::ast::PackageNode;
 use crate::ast::SchemaFile;
-use std::fs::File;
-use std::io::Write;
+use proc_macro2::TokenStream;
+use quote::format_ident;
+use quote::quote;
 use std::path::Path;
+use std::path::PathBuf;
+
+/// Where the modules declared for the nodes find their contents.
+#[derive(Clone, Copy)]
+enum Layout<'a> {
+    /// In files next to the declaring one.
+    Files,
+    /// In files under an absolute directory.
+    Entry(&'a Path),
+    /// In the declaring file itself.
+    Inline,
+}
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum ASTNode {
     PackageNode(PackageNode),
     SchemaNode(SchemaFile),
+    /// A module generated in another crate, re-exported from `path`.
+    ExternalModule {
+        name: String,
+        path: String,
+    },
 }
 
 impl ASTNode {
@@ -15,44 +38,167 @@ impl ASTNode {
         match self {
             Self::PackageNode(pn) => (pn.name.clone(), Vec::new()),
             Self::SchemaNode(schema) => (schema.name.clone(), schema.get_exports()),
+            Self::ExternalModule { name, .. } => (name.clone(), Vec::new()),
         }
     }
 
-    fn get_exports(data: &[Self]) -> Vec<(String, Vec<String>)> {
-        data.iter().map(Self::get_export).collect()
+    pub fn get_schemas(&self) -> Vec<&SchemaFile> {
+        match self {
+            Self::PackageNode(pn) => pn.inner.iter().flat_map(Self::get_schemas).collect(),
+            Self::SchemaNode(schema) => vec![schema],
+            Self::ExternalModule { .. } => Vec::new(),
+        }
     }
 
-    pub fn generate_mod_rs<P: AsRef<Path> + Clone>(
+    /// Declares `nodes` as modules, whose contents are found as `layout`
+    /// tells.
+    fn generate_modules(
         nodes: &[Self],
-        path: P,
-    ) -> Result<(), std::io::Error> {
-        std::fs::create_dir_all(path.clone()).map(|_| {
-            let mut file = File::create(path.clone().as_ref().join("mod.rs"))?;
-            for module in Self::get_exports(nodes) {
-                writeln!(
-                    file,
-                    "{}mod {};",
-                    if !module.1.is_empty() { "" } else { "pub " },
-                    module.0
-                )?;
-                for usage in module.1 {
-                    writeln!(file, "pub use {}::{};", module.0, usage)?;
+        strategy: ExportStrategy,
+        modules: &ModuleMap,
+        layout: Layout,
+    ) -> TokenStream {
+        let declarations = nodes.iter().map(|node| {
+            if let Self::ExternalModule { name, path } = node {
+                let path = syn::parse_str::<syn::Path>(path)
+                    .expect("the path is checked by `ModuleMap::with_standard_library`");
+                let name = ident(name);
+                return quote!(pub use #path::#name;);
+            }
+            let (name, exports) = match strategy {
+                ExportStrategy::Flatten => node.get_export(),
+                ExportStrategy::Modules => (node.get_export().0, Vec::new()),
+            };
+            let path = match node {
+                Self::SchemaNode(_) => format!("{}.rs", name),
+                _ => format!("{}/mod.rs", name),
+            };
+            // A nested module named after the file is re-exported under
+            // the file's name, so the file module itself is renamed.
+            let (module, renamed) = if exports.contains(&name) {
+                (format_ident!("{}_schema", name), true)
+            } else {
+                // Keywords such as `self` are declared under another name.
+                let renamed = rust_ident(&name) != name && !rust_ident(&name).starts_with("r#");
+                (ident(&name), renamed)
+            };
+            let path = match layout {
+                Layout::Entry(dir) => Some(dir.join(path).display().to_string()),
+                Layout::Files if renamed => Some(path),
+                Layout::Files | Layout::Inline => None,
+            }
+            .map(|path| quote!(#[path = #path]));
+            // Packages keep their case, which the entry file has no inner
+            // attribute to allow.
+            let lints = if matches!(layout, Layout::Entry(_))
+                && name.chars().any(|c| c.is_ascii_uppercase())
+            {
+                quote!(#[allow(non_snake_case)])
+            } else {
+                TokenStream::new()
+            };
+            let visibility = if exports.is_empty() {
+                quote!(pub)
+            } else {
+                TokenStream::new()
+            };
+            let contents = match (layout, node) {
+                (Layout::Inline, Self::SchemaNode(schema)) => {
+                    let contents = schema.generate(modules);
+                    quote!({ #contents })
                 }
+                (Layout::Inline, Self::PackageNode(pn)) => {
+                    let contents = Self::generate_modules(&pn.inner, strategy, modules, layout);
+                    quote!({
+                        #![allow(deprecated, non_camel_case_types, non_snake_case)]
+                        #contents
+                    })
+                }
+                _ => quote!(;),
+            };
+            let exports = exports.iter().map(|export| ident(export));
+            quote! {
+                #path
+                #lints
+                #visibility mod #module #contents
+                #(pub use #module::#exports;)*
             }
-            Ok(())
-        })?
+        });
+        quote!(#(#declarations)*)
     }
 
-    pub fn generate_node<P: AsRef<Path> + Clone>(&self, path: P) -> Result<(), std::io::Error> {
+    /// Renders the `mod.rs` declaring `nodes`.
+    ///
+    /// With `entry`, the directory the nodes are generated in, it renders
+    /// a file meant for `include!` instead: it has no inner attributes, which
+    /// the generated files hold anyway, and points each module to its
+    /// absolute path, as paths in an included file are relative to the file
+    /// including it.
+    pub fn render_mod_rs(
+        nodes: &[Self],
+        strategy: ExportStrategy,
+        modules: &ModuleMap,
+        entry: Option<&Path>,
+    ) -> String {
+        match entry {
+            Some(dir) => format!(
+                "// Generated by spatial_codegen, to `include!` in a module.\n{}",
+                pretty_print(Self::generate_modules(
+                    nodes,
+                    strategy,
+                    modules,
+                    Layout::Entry(dir)
+                ))
+            ),
+            None => {
+                let declarations = Self::generate_modules(nodes, strategy, modules, Layout::Files);
+                pretty_print(quote! {
+                    #![allow(deprecated, non_camel_case_types, non_snake_case)]
+                    #declarations
+                })
+            }
+        }
+    }
+
+    /// Renders a single file declaring `nodes` along with all their
+    /// contents, in nested modules. Like the entry of `render_mod_rs`, it
+    /// has no inner attributes at its root, so it can be included.
+    pub fn render_inline(nodes: &[Self], strategy: ExportStrategy, modules: &ModuleMap) -> String {
+        format!(
+            "// Generated by spatial_codegen, to `include!` in a module or declare as one.\n{}",
+            pretty_print(Self::generate_modules(
+                nodes,
+                strategy,
+                modules,
+                Layout::Inline
+            ))
+        )
+    }
+
+    /// Renders the files of the node, with their paths relative to the
+    /// directory the node is generated in, `path`.
+    pub fn render_node(
+        &self,
+        path: &Path,
+        strategy: ExportStrategy,
+        modules: &ModuleMap,
+        files: &mut Vec<(PathBuf, String)>,
+    ) {
         match self {
-            Self::SchemaNode(node) => node.generate_schema(path),
+            Self::SchemaNode(node) => {
+                files.push((path.join(node.name.clone() + ".rs"), node.render(modules)))
+            }
             Self::PackageNode(node) => {
-                let name = node.name.clone();
+                let path = path.join(&node.name);
                 for node in &node.inner {
-                    Self::generate_node(node, path.as_ref().join(&name))?;
+                    node.render_node(&path, strategy, modules, files);
                 }
-                Self::generate_mod_rs(&node.inner, path.as_ref().join(&name))
+                files.push((
+                    path.join("mod.rs"),
+                    Self::render_mod_rs(&node.inner, strategy, modules, None),
+                ));
             }
+            Self::ExternalModule { .. } => {}
         }
     }
 
@@ -84,7 +230,7 @@ impl ASTNode {
                     ASTNode::PackageNode(package_node)
                 }
             }
-            ASTNode::SchemaNode(s) => ASTNode::SchemaNode(s),
+            node => node,
         }
     }
 
@@ -1,8 +1,264 @@
+use crate::ast::generate_doc;
+use crate::ast::ident;
+use crate::ast::pascal_case;
 use crate::ast::DataType;
+use crate::ast::Located;
+use crate::ast::Nested;
+use crate::ast::Resolve;
+use crate::ast::SourceSpan;
+use crate::ast::Unresolved;
+use proc_macro2::Ident;
+use proc_macro2::Literal;
+use proc_macro2::Span;
+use proc_macro2::TokenStream;
+use quote::format_ident;
+use quote::quote;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Command {
     pub name: String,
+    pub doc: Option<String>,
     pub r_type: DataType,
     pub args: Vec<DataType>,
+    pub span: Option<SourceSpan>,
+}
+
+impl Command {
+    fn variant(&self) -> Ident {
+        ident(&pascal_case(&self.name))
+    }
+
+    fn index_const(&self) -> Ident {
+        format_ident!("{}_COMMAND_INDEX", self.name.to_uppercase())
+    }
+
+    /// Generates the commands of `component`: their indices, which start at
+    /// 1 in declaration order, enums wrapping their requests and responses,
+    /// a trait for the worker handling them, and helpers for the ones
+    /// sending them. Payloads nested in the component are taken from
+    /// `nested`'s module.
+    pub fn generate_multiple(data: &[Self], component: &str, nested: &Nested) -> TokenStream {
+        if data.is_empty() {
+            return TokenStream::new();
+        }
+        let module = nested.module_name();
+        let names = nested.get_names();
+        let name = ident(component);
+        let request = Ident::new(&format!("{}CommandRequest", component), Span::call_site());
+        let response = Ident::new(&format!("{}CommandResponse", component), Span::call_site());
+        let handler = Ident::new(&format!("{}CommandHandler", component), Span::call_site());
+        let docs = data
+            .iter()
+            .map(|command| generate_doc(&command.doc))
+            .collect::<Vec<TokenStream>>();
+        let variants = data.iter().map(Self::variant).collect::<Vec<Ident>>();
+        let methods = data
+            .iter()
+            .map(|command| ident(&command.name))
+            .collect::<Vec<Ident>>();
+        let index_consts = data.iter().map(Self::index_const).collect::<Vec<Ident>>();
+        let indices = (1..=data.len()).map(Literal::usize_unsuffixed);
+        let helpers = data
+            .iter()
+            .map(|command| format_ident!("{}_request", command.name));
+        let helper_docs = data.iter().map(|command| {
+            format!(
+                " Wraps a request for `{}`, to send to the worker with authority over `{}`.",
+                command.name, component
+            )
+        });
+        let into_responses = data
+            .iter()
+            .map(|command| format_ident!("into_{}", command.name));
+        let into_response_docs = data
+            .iter()
+            .map(|command| format!(" Takes the response to `{}`, if this is one.", command.name));
+        let requests = data
+            .iter()
+            .map(|command| command.args[0].qualify(&module, &names).rust_type())
+            .collect::<Vec<TokenStream>>();
+        let responses = data
+            .iter()
+            .map(|command| command.r_type.qualify(&module, &names).rust_type())
+            .collect::<Vec<TokenStream>>();
+        let request_doc = format!(" The requests of the commands of `{}`.", component);
+        let response_doc = format!(" The responses to the commands of `{}`.", component);
+        let handler_doc = format!(
+            " Handles the commands of `{}`, on the worker with authority over it.",
+            component
+        );
+        quote! {
+            #[doc = #request_doc]
+            #[derive(Debug)]
+            pub enum #request {
+                #(#docs #variants(#requests),)*
+            }
+
+            #[doc = #response_doc]
+            #[derive(Debug)]
+            pub enum #response {
+                #(#docs #variants(#responses),)*
+            }
+
+            impl #name {
+                #(
+                    pub const #index_consts: u32 = #indices;
+
+                    #[doc = #helper_docs]
+                    pub fn #helpers(request: #requests) -> #request {
+                        #request::#variants(request)
+                    }
+                )*
+            }
+
+            impl #request {
+                /// The index of the command this is for.
+                pub fn command_index(&self) -> u32 {
+                    match self {
+                        #(Self::#variants(_) => #name::#index_consts,)*
+                    }
+                }
+            }
+
+            impl #response {
+                /// The index of the command this is for.
+                pub fn command_index(&self) -> u32 {
+                    match self {
+                        #(Self::#variants(_) => #name::#index_consts,)*
+                    }
+                }
+
+                #(
+                    #[doc = #into_response_docs]
+                    pub fn #into_responses(self) -> ::std::option::Option<#responses> {
+                        match self {
+                            Self::#variants(response) => ::std::option::Option::Some(response),
+                            #[allow(unreachable_patterns)]
+                            _ => ::std::option::Option::None,
+                        }
+                    }
+                )*
+            }
+
+            #[doc = #handler_doc]
+            pub trait #handler {
+                #(#docs fn #methods(&mut self, request: #requests) -> #responses;)*
+
+                /// Passes `request` to the method of its command.
+                fn handle_request(&mut self, request: #request) -> #response {
+                    match request {
+                        #(#request::#variants(request) => #response::#variants(self.#methods(request)),)*
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks each command takes a single request type, as schemalang
+    /// specifies.
+    pub fn check_args(data: &[Self], parent: &str) -> Vec<Located> {
+        data.iter()
+            .filter(|command| command.args.len() != 1)
+            .map(|command| {
+                let message = format!(
+                    "command `{}` of `{}` takes {} request types, while a command takes exactly one",
+                    command.name,
+                    parent,
+                    command.args.len()
+                );
+                Located::new(message, command.span, &command.name)
+            })
+            .collect()
+    }
+
+    /// Checks each command is named apart from the others of its component,
+    /// the names of the variants and handler methods generated for them
+    /// included, and from the `handle_request` method of the handler.
+    pub fn check_names(data: &[Self], parent: &str) -> Vec<Located> {
+        data.iter()
+            .enumerate()
+            .filter_map(|(index, command)| {
+                let previous = &data[..index];
+                let message = if command.name == "handle_request" {
+                    format!(
+                        "command `{}` of `{}` is named like the method handling all of them",
+                        command.name, parent
+                    )
+                } else if previous.iter().any(|other| other.name == command.name) {
+                    format!(
+                        "command `{}` of `{}` is defined twice",
+                        command.name, parent
+                    )
+                } else if let Some((other, generated)) = previous.iter().find_map(|other| {
+                    if other.variant() == command.variant() {
+                        Some((other, command.variant()))
+                    } else if other.index_const() == command.index_const() {
+                        Some((other, command.index_const()))
+                    } else {
+                        None
+                    }
+                }) {
+                    format!(
+                        "command `{}` of `{}` is generated as `{}`, like `{}`",
+                        command.name, parent, generated, other.name
+                    )
+                } else {
+                    return None;
+                };
+                Some(Located::new(message, command.span, &command.name))
+            })
+            .collect()
+    }
+
+    pub fn resolve_references(data: &mut [Self], resolve: &Resolve, errors: &mut Vec<Unresolved>) {
+        for command in data {
+            command.r_type = command.r_type.resolve(resolve, command.span, errors);
+            for arg in &mut command.args {
+                *arg = arg.resolve(resolve, command.span, errors);
+            }
+        }
+    }
+
+    /// Checks each command takes and returns types.
+    pub fn check_types(
+        data: &[Self],
+        parent: &str,
+        is_enum: &dyn Fn(&str) -> bool,
+    ) -> Vec<Located> {
+        data.iter()
+            .flat_map(|command| {
+                let response = Some(&command.r_type)
+                    .filter(|r_type| !r_type.is_type(is_enum))
+                    .map(|r_type| ("returns", r_type));
+                command
+                    .args
+                    .iter()
+                    .filter(|arg| !arg.is_type(is_enum))
+                    .map(|arg| ("takes", arg))
+                    .chain(response)
+                    .map(move |(verb, payload)| {
+                        let message = format!(
+                            "command `{}` of `{}` {} `{}`, which is not a type",
+                            command.name,
+                            parent,
+                            verb,
+                            payload.schema_type()
+                        );
+                        Located::new(message, command.span, &command.name)
+                    })
+            })
+            .collect()
+    }
+
+    pub fn get_references(data: &[Self]) -> Vec<&str> {
+        data.iter()
+            .flat_map(|command| {
+                command
+                    .args
+                    .iter()
+                    .chain(std::iter::once(&command.r_type))
+                    .flat_map(DataType::get_references)
+            })
+            .collect()
+    }
 }
@@ -1,41 +1,417 @@
+use crate::ast::generate_doc;
+use crate::ast::ident;
+use crate::ast::type_graph::Field;
+use crate::ast::Annotation;
 use crate::ast::Command;
+use crate::ast::DataType;
+use crate::ast::Enum;
 use crate::ast::Event;
+use crate::ast::Located;
 use crate::ast::Member;
-use std::convert::identity;
+use crate::ast::Nested;
+use crate::ast::Resolve;
+use crate::ast::SourceSpan;
+use crate::ast::Type;
+use crate::ast::Unresolved;
+use proc_macro2::Ident;
+use proc_macro2::Literal;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+/// The type a component takes its fields from, with `data SomeType;`.
+///
+/// `members` is filled in once `type_name` has been resolved.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ComponentData {
+    pub type_name: String,
+    pub members: Vec<Member>,
+    pub span: Option<SourceSpan>,
+}
+
+/// The component ids user schemas cannot use, with what they are kept for.
+pub const RESERVED_IDS: &[(RangeInclusive<usize>, &str)] = &[
+    (0..=99, "the standard library"),
+    (190_000..=199_999, "the SpatialOS GDK for Unreal"),
+];
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Component {
     pub name: String,
+    pub doc: Option<String>,
+    pub annotations: Vec<Annotation>,
     pub id: usize,
     pub members: Vec<Member>,
     pub events: Vec<Event>,
     pub commands: Vec<Command>,
+    pub types: Vec<Type>,
+    pub enums: Vec<Enum>,
+    pub data: Option<ComponentData>,
+    pub span: Option<SourceSpan>,
 }
 
 impl Component {
-    pub fn generate_one(&self) -> String {
-        format!(
-            "{}\nstruct {} {{{}}}",
-            format!("#[spatial_component({})]", self.id),
-            self.name,
-            Member::generate_multiple(&self.members)
-        )
+    pub fn nested(&self) -> Nested<'_> {
+        Nested {
+            parent: &self.name,
+            types: &self.types,
+            enums: &self.enums,
+        }
+    }
+
+    /// Takes the fields of `data_type`, the type named by `data`, and
+    /// returns how they conflict with the component's own fields and
+    /// events.
+    pub fn expand_data(&mut self, data_type: &Type) -> Vec<Located> {
+        let data = match &mut self.data {
+            Some(data) => data,
+            None => return Vec::new(),
+        };
+        let mut conflicts = Vec::new();
+        for member in &self.members {
+            let message = if let Some(other) =
+                data_type.members.iter().find(|m| m.name == member.name)
+            {
+                format!(
+                    "field `{}` of component `{}` is already defined by its data type `{}`",
+                    other.name, self.name, data.type_name
+                )
+            } else if let Some(other) = data_type.members.iter().find(|m| m.id == member.id) {
+                format!(
+                    "field `{}` of component `{}` has id {}, already used by `{}` of its data type `{}`",
+                    member.name, self.name, member.id, other.name, data.type_name
+                )
+            } else {
+                continue;
+            };
+            conflicts.push(Located::new(message, member.span, &member.name));
+        }
+        conflicts.extend(Event::check_names(
+            &self.events,
+            &data_type.members,
+            &self.name,
+        ));
+        data.members = data_type.members.clone();
+        conflicts
+    }
+
+    /// All the fields of the component, the ones of its data type first.
+    pub fn get_members(&self) -> Vec<Member> {
+        self.data
+            .iter()
+            .flat_map(|data| data.members.iter())
+            .chain(self.members.iter())
+            .cloned()
+            .collect()
     }
 
-    pub fn generate_multiple(data: &[Self]) -> String {
+    fn generate_data_conversions(&self, data: &ComponentData) -> TokenStream {
+        let nested = self.nested();
+        let name = ident(&self.name);
+        let data_type = DataType::UserDefined(data.type_name.clone())
+            .qualify(&nested.module_name(), &nested.get_names())
+            .rust_type();
+        // The macros order the fields of `new` and `into_fields` by id.
+        let data_fields = Member::sort_by_id(&data.members)
+            .iter()
+            .map(|m| ident(&m.name))
+            .collect::<Vec<Ident>>();
+        let all_fields = Member::sort_by_id(&self.get_members())
+            .iter()
+            .map(|m| ident(&m.name))
+            .collect::<Vec<Ident>>();
+        let from_data = if self.members.is_empty() {
+            quote! {
+                impl From<#data_type> for #name {
+                    fn from(data: #data_type) -> Self {
+                        let (#(#data_fields,)*) = data.into_fields();
+                        Self::new(#(#data_fields,)*)
+                    }
+                }
+            }
+        } else {
+            let members = Member::sort_by_id(&nested.qualify(&self.members));
+            let parameters = members.iter().map(|m| ident(&m.name));
+            let types = members.iter().map(Member::rust_type);
+            quote! {
+                impl #name {
+                    pub fn from_data(data: #data_type, #(#parameters: #types),*) -> Self {
+                        let (#(#data_fields,)*) = data.into_fields();
+                        Self::new(#(#all_fields,)*)
+                    }
+                }
+            }
+        };
+        quote! {
+            #from_data
+
+            impl From<#name> for #data_type {
+                #[allow(unused_variables)]
+                fn from(component: #name) -> Self {
+                    let (#(#all_fields,)*) = component.into_fields();
+                    Self::new(#(#data_fields,)*)
+                }
+            }
+        }
+    }
+
+    /// Generates the fields of the component, then its events, which take
+    /// their index from their position among the component's events.
+    fn generate_fields(&self) -> TokenStream {
+        let nested = self.nested();
+        let fields = nested
+            .qualify(&self.get_members())
+            .iter()
+            .map(Member::generate_one)
+            .chain(
+                nested
+                    .qualify_events(&self.events)
+                    .iter()
+                    .enumerate()
+                    .map(|(index, event)| event.generate_one(index + 1)),
+            )
+            .collect::<Vec<TokenStream>>();
+        quote!(#(#fields),*)
+    }
+
+    pub fn generate_one(&self) -> TokenStream {
+        let nested = self.nested();
+        let doc = generate_doc(&self.doc);
+        let annotations = Annotation::generate_multiple(&self.annotations);
+        let id = Literal::usize_unsuffixed(self.id);
+        let name = ident(&self.name);
+        let fields = self.generate_fields();
+        let conversions = self
+            .data
+            .as_ref()
+            .map(|data| self.generate_data_conversions(data));
+        let commands = Command::generate_multiple(&self.commands, &self.name, &nested);
+        let nested = nested.generate();
+        quote! {
+            #doc
+            #annotations
+            #[spatial_component(#id)]
+            struct #name {
+                #fields
+            }
+
+            #conversions
+            #commands
+            #nested
+        }
+    }
+
+    pub fn generate_multiple(data: &[Self]) -> TokenStream {
+        data.iter().map(Component::generate_one).collect()
+    }
+
+    pub fn get_references(data: &[Self]) -> Vec<&str> {
         data.iter()
-            .map(Component::generate_one)
-            .fold(String::new(), |acc, val| acc + "\n\n" + &val)
+            .flat_map(|c| {
+                let mut references = Member::get_references(&c.members);
+                references.extend(Event::get_references(&c.events));
+                references.extend(Command::get_references(&c.commands));
+                references.extend(Type::get_references(&c.types));
+                references.extend(c.data.iter().map(|data| data.type_name.as_str()));
+                c.nested().filter_references(references)
+            })
+            .collect()
+    }
+
+    pub fn resolve_references(data: &mut [Self], resolve: &Resolve, errors: &mut Vec<Unresolved>) {
+        for c in data {
+            let resolve = c.nested().scope(resolve);
+            Member::resolve_references(&mut c.members, &resolve, errors);
+            Event::resolve_references(&mut c.events, &resolve, errors);
+            Command::resolve_references(&mut c.commands, &resolve, errors);
+            Type::resolve_references(&mut c.types, &resolve, errors);
+            if let Some(data) = &mut c.data {
+                // The fields of the data type are only copied in once it is
+                // resolved, so they are renamed along with the component.
+                Member::resolve_references(&mut data.members, &resolve, errors);
+                match resolve(&data.type_name) {
+                    Ok(name) => data.type_name = name,
+                    Err(message) => errors.push(Unresolved {
+                        name: data.type_name.clone(),
+                        message,
+                        span: data.span,
+                    }),
+                }
+            }
+        }
+    }
+
+    pub fn box_fields(data: &mut [Self], scope: &str, boxed: &HashSet<Field>) {
+        for c in data {
+            Type::box_fields(&mut c.types, &format!("{}.{}", scope, c.name), boxed);
+        }
+    }
+
+    pub fn check_types(data: &[Self], is_enum: &dyn Fn(&str) -> bool) -> Vec<Located> {
+        data.iter()
+            .flat_map(|c| {
+                let is_enum = c.nested().enum_scope(is_enum);
+                let mut errors = Member::check_types(&c.members, &c.name, &is_enum);
+                errors.extend(Event::check_types(&c.events, &c.name, &is_enum));
+                errors.extend(Command::check_types(&c.commands, &c.name, &is_enum));
+                errors.extend(Type::check_types(&c.types, &is_enum));
+                errors
+            })
+            .collect()
+    }
+
+    /// Checks the component id is out of the reserved ranges, and the ids
+    /// and event names within the component are unique, and commands take
+    /// a single request. The ids of components are checked
+    /// against each other once all the files are loaded.
+    pub fn check_ids(data: &[Self]) -> Vec<Located> {
+        data.iter()
+            .flat_map(|c| {
+                let mut errors = RESERVED_IDS
+                    .iter()
+                    .filter(|(range, _)| range.contains(&c.id))
+                    .map(|(range, owner)| {
+                        let message = format!(
+                            "component `{}` has id {}, while ids {} to {} are reserved for {}",
+                            c.name,
+                            c.id,
+                            range.start(),
+                            range.end(),
+                            owner
+                        );
+                        Located::new(message, c.span, &c.name)
+                    })
+                    .collect::<Vec<Located>>();
+                if c.id > u32::MAX as usize {
+                    let message = format!(
+                        "component `{}` has id {}, while ids go up to {}",
+                        c.name,
+                        c.id,
+                        u32::MAX
+                    );
+                    errors.push(Located::new(message, c.span, &c.name));
+                }
+                errors.extend(Member::check_ids(&c.members, &c.name));
+                errors.extend(Event::check_names(&c.events, &c.members, &c.name));
+                errors.extend(Command::check_args(&c.commands, &c.name));
+                errors.extend(Command::check_names(&c.commands, &c.name));
+                errors.extend(Enum::check_values(&c.enums));
+                errors.extend(Type::check_ids(&c.types));
+                errors
+            })
+            .collect()
     }
 
     pub fn get_export(&self) -> Option<String> {
         Some(self.name.clone())
     }
 
+    /// The definitions generated for the commands of the component, if any.
+    pub fn get_command_exports(&self) -> Vec<String> {
+        if self.commands.is_empty() {
+            return Vec::new();
+        }
+        ["CommandRequest", "CommandResponse", "CommandHandler"]
+            .iter()
+            .map(|suffix| format!("{}{}", self.name, suffix))
+            .collect()
+    }
+
     pub fn get_exports(data: &[Self]) -> Vec<String> {
         data.iter()
-            .map(Self::get_export)
-            .filter_map(identity)
+            .flat_map(|c| {
+                c.get_export()
+                    .into_iter()
+                    .chain(c.nested().get_export())
+                    .chain(c.get_command_exports())
+            })
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use crate::ast::pretty_print;
+    use crate::ast::Located;
+    use crate::ast::SchemaFile;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_generate_events() {
+        let (schema, errors) = SchemaFile::parse(
+            PathBuf::from("a.schema"),
+            "package test;\n\
+             component Health {\n\
+                 id = 1000;\n\
+                 [Deprecated]\n\
+                 uint32 hp = 1;\n\
+                 type Hit { uint32 points = 1; }\n\
+                 // Sent for every hit taken.\n\
+                 event Hit took_damage;\n\
+                 event test.Death died;\n\
+             }\n",
+        );
+        assert_eq!(errors, Vec::new());
+        let schema = schema.unwrap();
+        assert_eq!(schema.check_ids(), Vec::new());
+        let generated = pretty_print(schema.components[0].generate_one());
+        assert!(generated.contains(
+            "struct Health {\n    #[deprecated]\n    #[spatial(field = 1)]\n    hp: u32,\n    /// Sent for every hit taken.\n    #[spatial(event = 1)]\n    took_damage: health::Hit,\n    #[spatial(event = 2)]\n    died: schema_root::test::Death,\n}"
+        ));
+    }
+
+    #[test]
+    fn test_generate_commands() {
+        let (schema, errors) = SchemaFile::parse(
+            PathBuf::from("a.schema"),
+            "package test;\n\
+             component Health {\n\
+                 id = 1000;\n\
+                 type Hit { uint32 points = 1; }\n\
+                 command test.Empty heal(test.Empty);\n\
+                 // Takes `points` off the hit points.\n\
+                 command test.Empty take_damage(Hit);\n\
+             }\n",
+        );
+        assert_eq!(errors, Vec::new());
+        let generated = pretty_print(schema.unwrap().components[0].generate_one());
+        assert!(generated.contains(
+            "pub enum HealthCommandRequest {\n    Heal(schema_root::test::Empty),\n    /// Takes `points` off the hit points.\n    TakeDamage(health::Hit),\n}"
+        ));
+        assert!(generated.contains("    pub const TAKE_DAMAGE_COMMAND_INDEX: u32 = 2;\n"));
+        assert!(generated.contains(
+            "    /// Takes `points` off the hit points.\n    fn take_damage(&mut self, request: health::Hit) -> schema_root::test::Empty;\n"
+        ));
+        assert!(generated.contains(
+            "            HealthCommandRequest::TakeDamage(request) => {\n                HealthCommandResponse::TakeDamage(self.take_damage(request))\n            }\n"
+        ));
+    }
+
+    #[test]
+    fn test_generate_data_conversions_by_id() {
+        let (schema, errors) = SchemaFile::parse(
+            PathBuf::from("a.schema"),
+            "package test;\n\
+             type Orbit { float period = 2; float radius = 1; }\n\
+             component Body {\n\
+                 id = 1000;\n\
+                 data Orbit;\n\
+                 bool retrograde = 4;\n\
+                 uint32 moons = 3;\n\
+             }\n",
+        );
+        assert_eq!(errors, Vec::new());
+        let schema = schema.unwrap();
+        let mut component = schema.components[0].clone();
+        assert_eq!(
+            component.expand_data(&schema.types[0]),
+            Vec::<Located>::new()
+        );
+        let generated = pretty_print(component.generate_one());
+        assert!(generated.contains(
+            "    pub fn from_data(data: Orbit, moons: u32, retrograde: bool) -> Self {\n        let (radius, period) = data.into_fields();\n        Self::new(radius, period, moons, retrograde)\n"
+        ));
+    }
+}
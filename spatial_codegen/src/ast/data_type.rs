@@ -1,3 +1,13 @@
+use crate::ast::header::ROOT_MODULE;
+use crate::ast::rust_path;
+use crate::ast::Resolve;
+use crate::ast::SourceSpan;
+use crate::ast::Unresolved;
+use proc_macro2::Ident;
+use proc_macro2::Span;
+use proc_macro2::TokenStream;
+use quote::quote;
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum DataType {
     Bool,
@@ -24,26 +34,235 @@ pub enum DataType {
 }
 
 impl DataType {
-    pub fn rust_type(&self) -> String {
+    pub fn get_references(&self) -> Vec<&str> {
         match self {
-            Self::Bool => "bool".to_string(),
-            Self::Uint32 => "u32".to_string(),
-            Self::Uint64 => "u64".to_string(),
-            Self::Int32 => "i32".to_string(),
-            Self::Int64 => "i64".to_string(),
-            Self::Float => "f32".to_string(),
-            Self::Double => "f64".to_string(),
-            Self::String => "String".to_string(),
-            Self::Bytes => "Vec<u8>".to_string(),
-            Self::Map(fst, snd) => format!(
-                "std::collections::HashMap<{}, {}>",
-                (*fst).rust_type(),
-                (*snd).rust_type()
+            Self::Map(fst, snd) => {
+                let mut references = fst.get_references();
+                references.extend(snd.get_references());
+                references
+            }
+            Self::List(fst) | Self::Option(fst) => fst.get_references(),
+            Self::UserDefined(fst) => vec![fst.as_str()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Prefixes the references to `names` with `module`.
+    pub fn qualify(&self, module: &str, names: &[&str]) -> Self {
+        match self {
+            Self::Map(fst, snd) => Self::Map(
+                Box::new(fst.qualify(module, names)),
+                Box::new(snd.qualify(module, names)),
             ),
-            Self::List(fst) => format!("Vec<{}>", (*fst).rust_type()),
-            Self::Option(fst) => format!("Option<{}>", (*fst).rust_type()),
-            Self::UserDefined(fst) => fst.to_string(),
-            _ => "uninmplemented()!".to_string(),
+            Self::List(fst) => Self::List(Box::new(fst.qualify(module, names))),
+            Self::Option(fst) => Self::Option(Box::new(fst.qualify(module, names))),
+            Self::UserDefined(fst) if names.contains(&fst.as_str()) => {
+                Self::UserDefined(format!("{}::{}", module, fst))
+            }
+            _ => self.clone(),
+        }
+    }
+
+    /// Replaces the references with the names `resolve` gives them. The
+    /// ones it cannot resolve are kept, and recorded in `errors` along with
+    /// `span`, the node they were found in.
+    pub fn resolve(
+        &self,
+        resolve: &Resolve,
+        span: Option<SourceSpan>,
+        errors: &mut Vec<Unresolved>,
+    ) -> Self {
+        match self {
+            Self::Map(fst, snd) => Self::Map(
+                Box::new(fst.resolve(resolve, span, errors)),
+                Box::new(snd.resolve(resolve, span, errors)),
+            ),
+            Self::List(fst) => Self::List(Box::new(fst.resolve(resolve, span, errors))),
+            Self::Option(fst) => Self::Option(Box::new(fst.resolve(resolve, span, errors))),
+            Self::UserDefined(fst) => match resolve(fst) {
+                Ok(name) => Self::UserDefined(name),
+                Err(message) => {
+                    errors.push(Unresolved {
+                        name: fst.clone(),
+                        message,
+                        span,
+                    });
+                    self.clone()
+                }
+            },
+            _ => self.clone(),
+        }
+    }
+
+    /// Returns what schemalang does not allow in this type: an option of an
+    /// option, or a map key that cannot be hashed. `is_enum` tells which
+    /// references are enums, the only definitions that can be keys.
+    pub fn check(&self, is_enum: &dyn Fn(&str) -> bool) -> Vec<String> {
+        match self {
+            Self::Map(fst, snd) => {
+                let mut errors = match &**fst {
+                    Self::Float | Self::Double | Self::Bytes | Self::Entity => vec![format!(
+                        "`{}` cannot be a map key, as it cannot be hashed",
+                        fst.schema_type()
+                    )],
+                    Self::Map(..) | Self::List(_) | Self::Option(_) => vec![format!(
+                        "`{}` cannot be a map key, as collections cannot be hashed",
+                        fst.schema_type()
+                    )],
+                    Self::UserDefined(name) if !is_enum(name) => vec![format!(
+                        "`{}` cannot be a map key, as only primitives and enums can",
+                        name
+                    )],
+                    _ => Vec::new(),
+                };
+                errors.extend(fst.check(is_enum));
+                errors.extend(snd.check(is_enum));
+                errors
+            }
+            Self::Option(fst) => match &**fst {
+                Self::Option(_) => vec![format!(
+                    "`{}` is not allowed, as a missing value could not be told apart",
+                    self.schema_type()
+                )],
+                _ => fst.check(is_enum),
+            },
+            Self::List(fst) => fst.check(is_enum),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether the type is a user-defined type, as event and command
+    /// payloads have to be.
+    pub fn is_type(&self, is_enum: &dyn Fn(&str) -> bool) -> bool {
+        match self {
+            Self::UserDefined(name) => !is_enum(name),
+            _ => false,
         }
     }
+
+    /// Returns the type as written in a schema.
+    pub fn schema_type(&self) -> String {
+        match self {
+            Self::Bool => "bool".to_string(),
+            Self::Uint32 => "uint32".to_string(),
+            Self::Uint64 => "uint64".to_string(),
+            Self::Int32 => "int32".to_string(),
+            Self::Int64 => "int64".to_string(),
+            Self::SInt32 => "sint32".to_string(),
+            Self::SInt64 => "sint64".to_string(),
+            Self::Fixed32 => "fixed32".to_string(),
+            Self::Fixed64 => "fixed64".to_string(),
+            Self::SFixed32 => "sfixed32".to_string(),
+            Self::SFixed64 => "sfixed64".to_string(),
+            Self::Float => "float".to_string(),
+            Self::Double => "double".to_string(),
+            Self::String => "string".to_string(),
+            Self::Bytes => "bytes".to_string(),
+            Self::EntityID => "EntityId".to_string(),
+            Self::Entity => "Entity".to_string(),
+            Self::Map(fst, snd) => format!("map<{}, {}>", fst.schema_type(), snd.schema_type()),
+            Self::List(fst) => format!("list<{}>", fst.schema_type()),
+            Self::Option(fst) => format!("option<{}>", fst.schema_type()),
+            Self::UserDefined(fst) => fst.clone(),
+        }
+    }
+
+    /// Returns the type in Rust. The primitives Rust lacks, which are
+    /// encoded differently from their plain counterpart, come from the
+    /// `spatial_runtime` crate.
+    pub fn rust_type(&self) -> TokenStream {
+        match self {
+            Self::Bool => quote! { bool },
+            Self::Uint32 => quote! { u32 },
+            Self::Uint64 => quote! { u64 },
+            Self::Int32 => quote! { i32 },
+            Self::Int64 => quote! { i64 },
+            Self::SInt32 => quote! { ::spatial_runtime::SInt32 },
+            Self::SInt64 => quote! { ::spatial_runtime::SInt64 },
+            Self::Fixed32 => quote! { ::spatial_runtime::Fixed32 },
+            Self::Fixed64 => quote! { ::spatial_runtime::Fixed64 },
+            Self::SFixed32 => quote! { ::spatial_runtime::SFixed32 },
+            Self::SFixed64 => quote! { ::spatial_runtime::SFixed64 },
+            Self::Float => quote! { f32 },
+            Self::Double => quote! { f64 },
+            Self::String => quote! { ::std::string::String },
+            Self::Bytes => quote! { ::std::vec::Vec<u8> },
+            Self::EntityID => quote! { ::spatial_runtime::EntityId },
+            Self::Entity => quote! { ::spatial_runtime::Entity },
+            Self::Map(fst, snd) => {
+                let (fst, snd) = (fst.rust_type(), snd.rust_type());
+                quote! { ::std::collections::HashMap<#fst, #snd> }
+            }
+            Self::List(fst) => {
+                let fst = fst.rust_type();
+                quote! { ::std::vec::Vec<#fst> }
+            }
+            Self::Option(fst) => {
+                let fst = fst.rust_type();
+                quote! { ::std::option::Option<#fst> }
+            }
+            Self::UserDefined(fst) if fst.contains('.') => {
+                let root = Ident::new(ROOT_MODULE, Span::call_site());
+                let path = rust_path(&fst.replace('.', "::"));
+                quote! { #root::#path }
+            }
+            Self::UserDefined(fst) => rust_path(fst),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn user(name: &str) -> Box<DataType> {
+        Box::new(DataType::UserDefined(name.to_string()))
+    }
+
+    #[test]
+    fn test_check() {
+        let is_enum = |name: &str| name == "Kind";
+        let valid = DataType::Map(
+            user("Kind"),
+            Box::new(DataType::List(Box::new(DataType::Option(user("Hit"))))),
+        );
+        assert!(valid.check(&is_enum).is_empty());
+        assert_eq!(
+            DataType::Map(Box::new(DataType::Double), user("Hit")).check(&is_enum),
+            vec!["`double` cannot be a map key, as it cannot be hashed"]
+        );
+        assert_eq!(
+            DataType::Map(user("Hit"), user("Hit")).check(&is_enum),
+            vec!["`Hit` cannot be a map key, as only primitives and enums can"]
+        );
+        assert_eq!(
+            DataType::List(Box::new(DataType::Option(Box::new(DataType::Option(
+                user("Hit")
+            )))))
+            .check(&is_enum),
+            vec![
+                "`option<option<Hit>>` is not allowed, as a missing value could not be told apart"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_type() {
+        assert_eq!(
+            DataType::SInt32.rust_type().to_string(),
+            quote! { ::spatial_runtime::SInt32 }.to_string()
+        );
+        assert_eq!(
+            DataType::Map(Box::new(DataType::EntityID), Box::new(DataType::Entity))
+                .rust_type()
+                .to_string(),
+            quote! { ::std::collections::HashMap<::spatial_runtime::EntityId, ::spatial_runtime::Entity> }
+                .to_string()
+        );
+        assert_eq!(
+            DataType::List(user("test.type")).rust_type().to_string(),
+            quote! { ::std::vec::Vec<schema_root::test::r#type> }.to_string()
+        );
+    }
 }
@@ -0,0 +1,35 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// Renders a schema comment as `#[doc]` attributes, one per line, which
+/// are printed back as `///` lines.
+pub fn generate_doc(doc: &Option<String>) -> TokenStream {
+    let lines = doc.iter().flat_map(|doc| doc.lines()).map(|line| {
+        if line.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", line)
+        }
+    });
+    quote! { #(#[doc = #lines])* }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_generate_doc() {
+        assert!(generate_doc(&None).is_empty());
+        assert_eq!(
+            generate_doc(&Some("The health.\n\nIn \"points\".".to_string())).to_string(),
+            quote! {
+                #[doc = " The health."]
+                #[doc = ""]
+                #[doc = " In \"points\"."]
+            }
+            .to_string()
+        );
+    }
+}
@@ -1,40 +1,154 @@
+use crate::ast::generate_doc;
+use crate::ast::ident;
+use crate::ast::Annotation;
+use crate::ast::Located;
+use crate::ast::SourceSpan;
 use crate::ast::Value;
-use std::convert::identity;
+use proc_macro2::TokenStream;
+use quote::quote;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Enum {
     pub name: String,
+    pub doc: Option<String>,
+    pub annotations: Vec<Annotation>,
     pub values: Vec<Value>,
+    pub span: Option<SourceSpan>,
 }
 
 impl Enum {
-    pub fn generate_one(&self) -> String {
-        format!(
-            "{}\nenum {} {{{}}}\n",
-            "#[spatial_enum]",
-            self.name,
-            Value::generate_multiple(&self.values)
-        )
-    }
-
-    pub fn generate_multiple(data: &[Self]) -> String {
-        if !data.is_empty() {
-            data.iter()
-                .map(Self::generate_one)
-                .fold(String::new(), |acc, val| acc + "\n" + &val)
-        } else {
-            "".to_string()
+    pub fn generate_one(&self) -> TokenStream {
+        let doc = generate_doc(&self.doc);
+        let annotations = Annotation::generate_multiple(&self.annotations);
+        let name = ident(&self.name);
+        let values = Value::generate_multiple(&self.values);
+        quote! {
+            #doc
+            #annotations
+            #[spatial_enum]
+            enum #name {
+                #values
+            }
         }
     }
 
+    pub fn generate_multiple(data: &[Self]) -> TokenStream {
+        data.iter().map(Self::generate_one).collect()
+    }
+
+    /// Checks the names, variant names and values of each enum are unique,
+    /// and the values fit the `u32` the enum is generated with.
+    pub fn check_values(data: &[Self]) -> Vec<Located> {
+        data.iter()
+            .flat_map(|e| {
+                e.values
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(index, value)| {
+                        let previous = &e.values[..index];
+                        let located = |message| Some(Located::new(message, e.span, &e.name));
+                        if value.id > u32::MAX as usize {
+                            return located(format!(
+                                "value `{}` of enum `{}` is {}, while values go up to {}",
+                                value.name,
+                                e.name,
+                                value.id,
+                                u32::MAX
+                            ));
+                        }
+                        if previous.iter().any(|other| other.name == value.name) {
+                            return located(format!(
+                                "value `{}` of enum `{}` is defined twice",
+                                value.name, e.name
+                            ));
+                        }
+                        if let Some(other) = previous
+                            .iter()
+                            .find(|other| other.variant() == value.variant())
+                        {
+                            return located(format!(
+                                "value `{}` of enum `{}` is generated as `{}`, like `{}`",
+                                value.name,
+                                e.name,
+                                value.variant(),
+                                other.name
+                            ));
+                        }
+                        previous
+                            .iter()
+                            .find(|other| other.id == value.id)
+                            .and_then(|other| {
+                                located(format!(
+                                    "value `{}` of enum `{}` is {}, like `{}`",
+                                    value.name, e.name, value.id, other.name
+                                ))
+                            })
+                    })
+            })
+            .collect()
+    }
+
     pub fn get_export(&self) -> Option<String> {
         Some(self.name.clone())
     }
 
     pub fn get_exports(data: &[Self]) -> Vec<String> {
-        data.iter()
-            .map(Self::get_export)
-            .filter_map(identity)
-            .collect()
+        data.iter().filter_map(Self::get_export).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn value(name: &str, id: usize) -> Value {
+        Value {
+            name: name.to_string(),
+            doc: None,
+            id,
+        }
+    }
+
+    #[test]
+    fn test_generate_one() {
+        let e = Enum {
+            name: "LifeState".to_string(),
+            doc: None,
+            annotations: Vec::new(),
+            values: vec![value("ALIVE", 0), value("LOST_HOPE", 3)],
+            span: None,
+        };
+        assert_eq!(
+            e.generate_one().to_string(),
+            quote! {
+                #[spatial_enum]
+                enum LifeState {
+                    #[spatial(name = "ALIVE")]
+                    Alive = 0,
+                    #[spatial(name = "LOST_HOPE")]
+                    LostHope = 3
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_check_variants() {
+        let e = Enum {
+            name: "Kind".to_string(),
+            doc: None,
+            annotations: Vec::new(),
+            values: vec![value("LEVEL_1", 0), value("LEVEL1", 1)],
+            span: None,
+        };
+        assert_eq!(
+            Enum::check_values(&[e])
+                .into_iter()
+                .map(|error| error.message)
+                .collect::<Vec<_>>(),
+            vec!["value `LEVEL1` of enum `Kind` is generated as `Level1`, like `LEVEL_1`"]
+        );
     }
 }
@@ -1,7 +1,100 @@
+use crate::ast::generate_doc;
+use crate::ast::ident;
 use crate::ast::DataType;
+use crate::ast::Located;
+use crate::ast::Member;
+use crate::ast::Resolve;
+use crate::ast::SourceSpan;
+use crate::ast::Unresolved;
+use proc_macro2::Literal;
+use proc_macro2::TokenStream;
+use quote::quote;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Event {
     pub name: String,
+    pub doc: Option<String>,
     pub r_type: DataType,
+    pub span: Option<SourceSpan>,
+}
+
+impl Event {
+    /// Generates the event as a field of its component, which the
+    /// `spatial_component` macro turns into a queue. `index` is the stable
+    /// index of the event within its component, starting at 1.
+    pub fn generate_one(&self, index: usize) -> TokenStream {
+        let doc = generate_doc(&self.doc);
+        let index = Literal::usize_unsuffixed(index);
+        let name = ident(&self.name);
+        let r_type = self.r_type.rust_type();
+        quote! {
+            #doc
+            #[spatial(event = #index)]
+            #name: #r_type
+        }
+    }
+
+    /// Checks each event is named apart from the fields and the other
+    /// events of its component, as they share the generated struct.
+    pub fn check_names(data: &[Self], members: &[Member], parent: &str) -> Vec<Located> {
+        data.iter()
+            .enumerate()
+            .filter_map(|(index, event)| {
+                let message = if members.iter().any(|member| member.name == event.name) {
+                    format!(
+                        "event `{}` of `{}` is named like one of its fields",
+                        event.name, parent
+                    )
+                } else if data[..index].iter().any(|other| other.name == event.name) {
+                    format!("event `{}` of `{}` is defined twice", event.name, parent)
+                } else if let Some(other) = data[..index]
+                    .iter()
+                    .find(|other| other.name.to_uppercase() == event.name.to_uppercase())
+                {
+                    format!(
+                        "event `{}` of `{}` is generated as `{}_EVENT_INDEX`, like `{}`",
+                        event.name,
+                        parent,
+                        event.name.to_uppercase(),
+                        other.name
+                    )
+                } else {
+                    return None;
+                };
+                Some(Located::new(message, event.span, &event.name))
+            })
+            .collect()
+    }
+
+    pub fn resolve_references(data: &mut [Self], resolve: &Resolve, errors: &mut Vec<Unresolved>) {
+        for event in data {
+            event.r_type = event.r_type.resolve(resolve, event.span, errors);
+        }
+    }
+
+    /// Checks each event carries a type.
+    pub fn check_types(
+        data: &[Self],
+        parent: &str,
+        is_enum: &dyn Fn(&str) -> bool,
+    ) -> Vec<Located> {
+        data.iter()
+            .filter(|event| !event.r_type.is_type(is_enum))
+            .map(|event| {
+                let message = format!(
+                    "event `{}` of `{}` carries `{}`, which is not a type",
+                    event.name,
+                    parent,
+                    event.r_type.schema_type()
+                );
+                Located::new(message, event.span, &event.name)
+            })
+            .collect()
+    }
+
+    pub fn get_references(data: &[Self]) -> Vec<&str> {
+        data.iter()
+            .flat_map(|event| event.r_type.get_references())
+            .collect()
+    }
 }
@@ -0,0 +1,269 @@
+use crate::ast::SchemaFile;
+use crate::ast::SourceSpan;
+use crate::diagnostic::Diagnostic;
+use crate::diagnostic::Span;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// How a package module exposes the definitions of its files.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default)]
+pub enum ExportStrategy {
+    /// The definitions of every file are re-exported by the package, so
+    /// they share its namespace, as in `io::nebulis::Mass`.
+    #[default]
+    Flatten,
+    /// Each file is a public module of its package, so definitions of
+    /// different files may share a name, as in `io::nebulis::physics::Mass`.
+    /// References may then name the file too, as in `io.nebulis.physics.Mass`.
+    Modules,
+}
+
+/// A name declared in the module of a package or of a file.
+struct Declaration<'a> {
+    name: String,
+    /// What the name stands for, as in "type `Damage`".
+    what: String,
+    schema: &'a SchemaFile,
+    range: Option<Range<usize>>,
+}
+
+impl<'a> Declaration<'a> {
+    fn module(name: String, what: String, schema: &'a SchemaFile) -> Self {
+        Self {
+            name,
+            what,
+            schema,
+            range: None,
+        }
+    }
+
+    fn location(&self) -> String {
+        match &self.range {
+            Some(range) => {
+                let start = Span::new(&self.schema.source, range.clone()).start;
+                format!(
+                    "{}:{}:{}",
+                    self.schema.path.display(),
+                    start.line,
+                    start.column
+                )
+            }
+            None => format!("\"{}\"", self.schema.path.display()),
+        }
+    }
+
+    fn clash(&self, first: &Self) -> Diagnostic {
+        let diagnostic = Diagnostic::new(
+            &self.schema.path,
+            format!(
+                "{} clashes with {} at {}",
+                self.what,
+                first.what,
+                first.location()
+            ),
+        );
+        match &self.range {
+            Some(range) => diagnostic.with_span(&self.schema.source, range.clone()),
+            None => diagnostic,
+        }
+    }
+}
+
+/// Declares `name`, defined in `schema` at `span`, along with the module of
+/// the definitions nested in it, if any.
+fn declare<'a>(
+    declarations: &mut Vec<Declaration<'a>>,
+    schema: &'a SchemaFile,
+    what: &str,
+    name: &str,
+    span: Option<SourceSpan>,
+    nested: Option<String>,
+) {
+    let range = span.map(|span| span.find(&schema.source, name));
+    declarations.push(Declaration {
+        name: name.to_string(),
+        what: format!("{} `{}`", what, name),
+        schema,
+        range: range.clone(),
+    });
+    if let Some(module) = nested {
+        declarations.push(Declaration {
+            what: format!(
+                "module `{}` of the definitions nested in `{}`",
+                module, name
+            ),
+            name: module,
+            schema,
+            range,
+        });
+    }
+}
+
+/// Returns the definitions `schema` exports: its types, enums and
+/// components, the modules holding their nested definitions, and the
+/// definitions generated for the commands of components.
+fn get_definitions(schema: &SchemaFile) -> Vec<Declaration<'_>> {
+    let mut declarations = Vec::new();
+    for t in &schema.types {
+        let nested = t.nested().get_export();
+        declare(&mut declarations, schema, "type", &t.name, t.span, nested);
+    }
+    for e in &schema.enums {
+        declare(&mut declarations, schema, "enum", &e.name, e.span, None);
+    }
+    for c in &schema.components {
+        let nested = c.nested().get_export();
+        declare(
+            &mut declarations,
+            schema,
+            "component",
+            &c.name,
+            c.span,
+            nested,
+        );
+        for name in c.get_command_exports() {
+            declarations.push(Declaration {
+                what: format!(
+                    "`{}`, generated for the commands of component `{}`",
+                    name, c.name
+                ),
+                name,
+                schema,
+                range: c.span.map(|span| span.find(&schema.source, &c.name)),
+            });
+        }
+    }
+    declarations
+}
+
+/// Reports the names declared twice in a module, at the second one.
+fn check_namespace(declarations: &[Declaration]) -> Vec<Diagnostic> {
+    declarations
+        .iter()
+        .enumerate()
+        .filter_map(|(index, declaration)| {
+            declarations[..index]
+                .iter()
+                .find(|first| first.name == declaration.name)
+                .map(|first| declaration.clash(first))
+        })
+        .collect()
+}
+
+/// Reports the names which would be declared twice in the generated
+/// modules, such as two files of a package defining the same type when
+/// their definitions are flattened, or a file named like a subpackage.
+pub fn check_exports<'a, I>(schemas: I, strategy: ExportStrategy) -> Vec<Diagnostic>
+where
+    I: IntoIterator<Item = &'a SchemaFile>,
+{
+    let mut packages: BTreeMap<Vec<String>, Vec<&SchemaFile>> = BTreeMap::new();
+    for schema in schemas {
+        for depth in 0..schema.package_name.len() {
+            packages
+                .entry(schema.package_name[..depth].to_vec())
+                .or_default();
+        }
+        packages
+            .entry(schema.package_name.clone())
+            .or_default()
+            .push(schema);
+    }
+    let mut errors = Vec::new();
+    for (package, files) in &packages {
+        let mut modules = Vec::new();
+        let mut items = Vec::new();
+        for schema in files {
+            let definitions = get_definitions(schema);
+            // See `ASTNode::generate_mod_rs`.
+            let renamed = strategy == ExportStrategy::Flatten
+                && definitions.iter().any(|d| d.name == schema.name);
+            let module = if renamed {
+                format!("{}_schema", schema.name)
+            } else {
+                schema.name.clone()
+            };
+            modules.push(Declaration::module(
+                module.clone(),
+                format!("module `{}` of \"{}\"", module, schema.path.display()),
+                schema,
+            ));
+            match strategy {
+                ExportStrategy::Flatten => items.extend(definitions),
+                ExportStrategy::Modules => errors.extend(check_namespace(&definitions)),
+            }
+        }
+        // A subpackage is declared as a module, located at one of its files.
+        let subpackages = packages
+            .iter()
+            .filter(|(other, _)| other.len() == package.len() + 1 && other.starts_with(package))
+            .filter_map(|(other, _)| {
+                let schema = packages
+                    .iter()
+                    .filter(|(inner, _)| inner.starts_with(other))
+                    .find_map(|(_, files)| files.first())?;
+                Some(Declaration::module(
+                    other[package.len()].clone(),
+                    format!("package `{}`", other.join(".")),
+                    schema,
+                ))
+            });
+        modules.extend(subpackages);
+        modules.extend(items);
+        errors.extend(check_namespace(&modules));
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::path::PathBuf;
+
+    fn parse(path: &str, source: &str) -> SchemaFile {
+        let (schema, errors) = SchemaFile::parse(PathBuf::from(path), source);
+        assert_eq!(errors, Vec::new());
+        schema.unwrap()
+    }
+
+    #[test]
+    fn test_check_exports() {
+        let a = parse("a.schema", "package test;\ntype Damage {}\n");
+        let b = parse("b.schema", "package test;\n\ntype Damage {}\n");
+        let player = parse("player.schema", "package test;\n");
+        let health = parse("player/health.schema", "package test.player;\n");
+        let schemas = vec![&a, &b, &player, &health];
+        let source = &b.source;
+        let start = source.find("Damage").unwrap();
+        assert_eq!(
+            check_exports(schemas.clone(), ExportStrategy::Flatten),
+            vec![
+                Diagnostic::new(
+                    "player/health.schema",
+                    "package `test.player` clashes with module `player` of \"player.schema\" at \"player.schema\""
+                ),
+                Diagnostic::new(
+                    "b.schema",
+                    "type `Damage` clashes with type `Damage` at a.schema:2:6"
+                )
+                .with_span(source, start..start + "Damage".len()),
+            ]
+        );
+        assert_eq!(check_exports(schemas, ExportStrategy::Modules).len(), 1);
+    }
+
+    #[test]
+    fn test_check_exports_within_file() {
+        let a = parse(
+            "a.schema",
+            "package test;\ntype Damage {}\nenum Damage { A = 0; }\n",
+        );
+        let errors = check_exports(vec![&a], ExportStrategy::Modules);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            "enum `Damage` clashes with type `Damage` at a.schema:2:6"
+        );
+    }
+}
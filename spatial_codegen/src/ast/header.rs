@@ -1,13 +1,29 @@
+use proc_macro2::Ident;
+use proc_macro2::Span;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// The alias every generated file gives the root of the generated modules,
+/// which package-qualified references start from.
+pub const ROOT_MODULE: &str = "schema_root";
+
 pub struct Header {}
 
 impl Header {
-    pub fn generate() -> String {
-        format!(
-            "{}\n{}\n{}\n{}\n",
-            "#[allow(unused_imports)]\nuse spatial_macro::spatial_enum;",
-            "#[allow(unused_imports)]\nuse spatial_macro::spatial_type;",
-            "#[allow(unused_imports)]\nuse spatial_macro::spatial_component;",
-            "#[allow(unused_imports)]\nuse std::collections::HashMap;"
-        )
+    /// `root` is the path of the root module from the file.
+    pub fn generate(root: TokenStream) -> TokenStream {
+        let alias = Ident::new(ROOT_MODULE, Span::call_site());
+        quote! {
+            #![allow(deprecated, non_camel_case_types, non_snake_case)]
+
+            #[allow(unused_imports)]
+            use spatial_macro::spatial_enum;
+            #[allow(unused_imports)]
+            use spatial_macro::spatial_type;
+            #[allow(unused_imports)]
+            use spatial_macro::spatial_component;
+            #[allow(unused_imports)]
+            use #root as #alias;
+        }
     }
 }
@@ -0,0 +1,106 @@
+use proc_macro2::Ident;
+use proc_macro2::Span;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+/// Keywords that cannot be used as raw identifiers either.
+const PATH_KEYWORDS: &[&str] = &["_", "crate", "self", "Self", "super"];
+
+/// Returns `name` as a Rust identifier: keywords become raw identifiers,
+/// and the few that cannot be get a trailing `_`.
+pub fn rust_ident(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else if PATH_KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Whether `name` can be made a Rust identifier by `rust_ident`: ASCII
+/// letters, digits and underscores, not starting with a digit.
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    name != "_"
+        && matches!(chars.next(), Some(first) if first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Like `rust_ident`, as an identifier token.
+pub fn ident(name: &str) -> Ident {
+    if KEYWORDS.contains(&name) {
+        Ident::new_raw(name, Span::call_site())
+    } else {
+        Ident::new(&rust_ident(name), Span::call_site())
+    }
+}
+
+/// Like `ident`, for each segment of a `::` separated path.
+pub fn rust_path(path: &str) -> TokenStream {
+    let segments = path.split("::").map(ident);
+    quote! { #(#segments)::* }
+}
+
+/// Returns a schema name in `PascalCase`, as Rust names types and
+/// variants. Each word between underscores starts with an uppercase
+/// letter, and the rest of the words written in uppercase goes lowercase,
+/// so both `took_damage` and `TOOK_DAMAGE` become `TookDamage`, while
+/// `maxHp` becomes `MaxHp`. A name not starting with a letter then keeps a
+/// leading `_`.
+pub fn pascal_case(name: &str) -> String {
+    let pascal = name
+        .split('_')
+        .flat_map(|word| {
+            let uppercase = !word.chars().any(|c| c.is_ascii_lowercase());
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars.map(move |c| if uppercase { c.to_ascii_lowercase() } else { c }))
+        })
+        .collect::<String>();
+    if pascal.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        pascal
+    } else {
+        format!("_{}", pascal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_rust_ident() {
+        assert_eq!(rust_ident("hp"), "hp");
+        assert_eq!(rust_ident("type"), "r#type");
+        assert_eq!(rust_ident("Self"), "Self_");
+        assert_eq!(
+            rust_path("type::Self").to_string(),
+            quote! { r#type::Self_ }.to_string()
+        );
+        assert!(is_identifier("health_2"));
+        assert!(!is_identifier("my-file"));
+        assert!(!is_identifier("2d"));
+        assert!(!is_identifier("_"));
+        assert_eq!(pascal_case("took_damage"), "TookDamage");
+        assert_eq!(pascal_case("RESPAWNING_2"), "Respawning2");
+        assert_eq!(pascal_case("Alive"), "Alive");
+        assert_eq!(pascal_case("maxHp"), "MaxHp");
+        assert_eq!(pascal_case("hp__x_"), "HpX");
+        assert_eq!(pascal_case("_2"), "_2");
+        assert_eq!(pascal_case("_"), "_");
+        assert_eq!(rust_ident("_"), "__");
+    }
+}
@@ -0,0 +1,635 @@
+use crate::ast::module_map::STANDARD_LIBRARY;
+use crate::ast::Enum;
+use crate::ast::ExportStrategy;
+use crate::ast::Nested;
+use crate::ast::SchemaFile;
+use crate::ast::Type;
+use crate::diagnostic::Diagnostic;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Whether `schema` is one of the built-in files of the standard library,
+/// which have no source.
+fn is_standard_library(schema: &SchemaFile) -> bool {
+    schema.source.is_empty()
+        && schema.package_name.first().map(String::as_str) == Some(STANDARD_LIBRARY)
+}
+
+/// Keeps the files of `found` closest to `schema`, which hide the others:
+/// `schema` itself, or else those of the innermost package, in import path
+/// order.
+fn closest<'b>(schema: &SchemaFile, mut found: Vec<&'b SchemaFile>) -> Vec<&'b SchemaFile> {
+    if found.iter().any(|file| file.path == schema.path) {
+        found.retain(|file| file.path == schema.path);
+    }
+    if let Some(depth) = found.iter().map(|file| file.package_name.len()).max() {
+        found.retain(|file| file.package_name.len() == depth);
+    }
+    found.sort_by(|a, b| a.import_path.cmp(&b.import_path));
+    found
+}
+
+pub struct ImportGraph<'a> {
+    files: HashMap<&'a Path, &'a SchemaFile>,
+    exports: ExportStrategy,
+}
+
+impl<'a> ImportGraph<'a> {
+    pub fn new<I: IntoIterator<Item = &'a SchemaFile>>(schemas: I) -> Self {
+        Self {
+            files: schemas
+                .into_iter()
+                .map(|schema| (schema.import_path.as_path(), schema))
+                .collect(),
+            exports: ExportStrategy::default(),
+        }
+    }
+
+    /// Resolves names to the scopes `exports` generates definitions in, see
+    /// `SchemaFile::scope`.
+    pub fn with_exports(mut self, exports: ExportStrategy) -> Self {
+        self.exports = exports;
+        self
+    }
+
+    /// Whether names qualified with `package` may refer to the definitions
+    /// of `file`: those of its package, or of its scope once resolved.
+    fn is_in(&self, file: &SchemaFile, package: &str) -> bool {
+        file.package_name.join(".") == package || file.scope(self.exports) == package
+    }
+
+    fn get_imports(&self, schema: &SchemaFile) -> Vec<&'a SchemaFile> {
+        schema
+            .imports
+            .iter()
+            .filter_map(|import| self.files.get(Path::new(import)).copied())
+            .collect()
+    }
+
+    fn check_missing(&self, schema: &SchemaFile) -> Vec<Diagnostic> {
+        schema
+            .imports
+            .iter()
+            .filter(|import| !self.files.contains_key(Path::new(import)))
+            .map(|import| {
+                Diagnostic::new(
+                    &schema.path,
+                    format!("cannot find imported file \"{}\"", import),
+                )
+            })
+            .collect()
+    }
+
+    fn find_cycle(
+        &self,
+        schema: &'a SchemaFile,
+        stack: &mut Vec<&'a Path>,
+        visited: &mut HashSet<&'a Path>,
+    ) -> Option<Vec<&'a Path>> {
+        for import in self.get_imports(schema) {
+            if import.import_path == stack[0] {
+                let mut cycle = stack.clone();
+                cycle.push(import.import_path.as_path());
+                return Some(cycle);
+            }
+            if visited.insert(import.import_path.as_path()) {
+                stack.push(import.import_path.as_path());
+                if let Some(cycle) = self.find_cycle(import, stack, visited) {
+                    return Some(cycle);
+                }
+                stack.pop();
+            }
+        }
+        None
+    }
+
+    fn check_cycle(&self, schema: &'a SchemaFile) -> Vec<Diagnostic> {
+        let mut stack = vec![schema.import_path.as_path()];
+        self.find_cycle(schema, &mut stack, &mut HashSet::new())
+            .map(|cycle| {
+                let cycle = cycle
+                    .iter()
+                    .map(|path| format!("\"{}\"", path.display()))
+                    .collect::<Vec<String>>()
+                    .join(" -> ");
+                vec![Diagnostic::new(
+                    &schema.path,
+                    format!("circular import {}", cycle),
+                )]
+            })
+            .unwrap_or_default()
+    }
+
+    fn get_package(&self, package: &str) -> Vec<&'a SchemaFile> {
+        let mut files = self
+            .files
+            .values()
+            .filter(|file| self.is_in(file, package))
+            .copied()
+            .collect::<Vec<&SchemaFile>>();
+        files.sort_by(|a, b| a.import_path.cmp(&b.import_path));
+        files
+    }
+
+    /// Returns the name of the type or enum `reference` stands for in
+    /// `schema`, qualified with the scope of the file defining it, or why
+    /// there is none.
+    ///
+    /// The name has to be defined in `schema` or in a file it imports,
+    /// within its package when it is qualified, and within the package of
+    /// `schema` or one enclosing it when it is not. A definition of `schema`
+    /// hides the others, as do those of a package the ones of the packages
+    /// enclosing it, but a name defined by several other files is ambiguous.
+    ///
+    /// A definition nested in a type or a component is named after its
+    /// parent, as in `Health.Kind`, and resolved to the name of its parent
+    /// followed by its own.
+    pub fn resolve(&self, schema: &SchemaFile, reference: &str) -> Result<String, String> {
+        let (files, name) = self.get_candidates(schema, reference);
+        let defines = |file: &&SchemaFile| {
+            file.types.iter().any(|t| t.name == name) || file.enums.iter().any(|e| e.name == name)
+        };
+        let hidden = match reference.rfind('.') {
+            Some(index) => self
+                .get_package(&reference[..index])
+                .into_iter()
+                .find(|file| defines(file)),
+            None => self
+                .get_imports(schema)
+                .into_iter()
+                .find(|file| defines(file)),
+        };
+        let found = closest(schema, files.iter().copied().filter(defines).collect());
+        if found.is_empty() {
+            if let Some(nested) = self.resolve_nested(schema, reference) {
+                return nested;
+            }
+        }
+        match found[..] {
+            [file] => Ok(format!("{}.{}", file.scope(self.exports), name)),
+            [first, second, ..] => Err(format!(
+                "`{}` is ambiguous, as it is defined in \"{}\" and \"{}\"",
+                name,
+                first.import_path.display(),
+                second.import_path.display()
+            )),
+            [] if files
+                .iter()
+                .any(|file| file.components.iter().any(|c| c.name == name)) =>
+            {
+                Err(format!("`{}` is a component, not a type or an enum", name))
+            }
+            [] => match hidden {
+                Some(file) if !reference.contains('.') => Err(format!(
+                    "`{}` is defined in package `{}`, so it has to be qualified as `{}.{}`",
+                    name,
+                    file.package_name.join("."),
+                    file.package_name.join("."),
+                    name
+                )),
+                Some(file) => Err(format!(
+                    "`{}` is defined in \"{}\", which this file does not import",
+                    reference,
+                    file.import_path.display()
+                )),
+                None => Err(match reference.rfind('.') {
+                    Some(index) => format!(
+                        "`{}` is not defined in package `{}`",
+                        name,
+                        &reference[..index]
+                    ),
+                    None => format!(
+                        "`{}` is not defined in this file or in any file it imports",
+                        name
+                    ),
+                }),
+            },
+        }
+    }
+
+    /// Resolves `reference` as a definition nested in a type or a
+    /// component, as in `Health.Kind`, to the resolved name of its parent
+    /// followed by its own. Returns `None` when the reference names no
+    /// parent.
+    fn resolve_nested(
+        &self,
+        schema: &SchemaFile,
+        reference: &str,
+    ) -> Option<Result<String, String>> {
+        let index = reference.rfind('.')?;
+        let (parent, nested) = self.find_parent(schema, &reference[..index])?;
+        let name = &reference[index + 1..];
+        Some(if nested.get_names().contains(&name) {
+            Ok(format!("{}.{}", parent, name))
+        } else {
+            Err(format!(
+                "`{}` is not defined in `{}`",
+                name,
+                &reference[..index]
+            ))
+        })
+    }
+
+    /// Looks up the type or component `reference` stands for in `schema`,
+    /// nested ones included, and returns its resolved name along with the
+    /// definitions nested in it.
+    fn find_parent<'b>(
+        &self,
+        schema: &'b SchemaFile,
+        reference: &str,
+    ) -> Option<(String, Nested<'b>)>
+    where
+        'a: 'b,
+    {
+        let (files, name) = self.get_candidates(schema, reference);
+        let defines = |file: &&SchemaFile| {
+            file.types.iter().any(|t| t.name == name)
+                || file.components.iter().any(|c| c.name == name)
+        };
+        if let [file] = closest(schema, files.into_iter().filter(defines).collect())[..] {
+            let nested = match file.types.iter().find(|t| t.name == name) {
+                Some(t) => t.nested(),
+                None => file.components.iter().find(|c| c.name == name)?.nested(),
+            };
+            return Some((format!("{}.{}", file.scope(self.exports), name), nested));
+        }
+        let index = reference.rfind('.')?;
+        let (parent, nested) = self.find_parent(schema, &reference[..index])?;
+        let name = &reference[index + 1..];
+        let t = nested.types.iter().find(|t| t.name == name)?;
+        Some((format!("{}.{}", parent, name), t.nested()))
+    }
+
+    /// Returns the files `name` may be defined in when referred to from
+    /// `schema`, along with its unqualified name: `schema` then the files it
+    /// imports, only those of its package when it is qualified, and
+    /// otherwise those of the package of `schema` and of the ones enclosing
+    /// it, innermost first.
+    ///
+    /// The files of the standard library are visible from every file, so
+    /// qualified names such as `improbable.Coordinates` need no import.
+    fn get_candidates<'b, 'c>(
+        &self,
+        schema: &'b SchemaFile,
+        name: &'c str,
+    ) -> (Vec<&'b SchemaFile>, &'c str)
+    where
+        'a: 'b,
+    {
+        match name.rfind('.') {
+            Some(index) => {
+                let package = &name[..index];
+                let mut files = std::iter::once(schema)
+                    .chain(self.get_imports(schema))
+                    .collect::<Vec<&SchemaFile>>();
+                files.extend(
+                    self.get_package(package)
+                        .into_iter()
+                        .filter(|file| is_standard_library(file)),
+                );
+                let mut visible = Vec::new();
+                for file in files {
+                    if self.is_in(file, package)
+                        && visible
+                            .iter()
+                            .all(|other: &&SchemaFile| other.path != file.path)
+                    {
+                        visible.push(file);
+                    }
+                }
+                (visible, &name[index + 1..])
+            }
+            None => {
+                let mut files = std::iter::once(schema)
+                    .chain(
+                        self.get_imports(schema)
+                            .into_iter()
+                            .filter(|file| schema.package_name.starts_with(&file.package_name)),
+                    )
+                    .collect::<Vec<&SchemaFile>>();
+                files.sort_by_key(|file| std::cmp::Reverse(file.package_name.len()));
+                (files, name)
+            }
+        }
+    }
+
+    /// Looks `name` up among the types it may be defined in, see
+    /// `get_candidates`.
+    pub fn find_type<'b>(&self, schema: &'b SchemaFile, name: &str) -> Option<&'b Type>
+    where
+        'a: 'b,
+    {
+        let (files, short) = self.get_candidates(schema, name);
+        files
+            .into_iter()
+            .flat_map(|schema| schema.types.iter())
+            .find(|t| t.name == short)
+            .or_else(|| {
+                let index = name.rfind('.')?;
+                let (_, nested) = self.find_parent(schema, &name[..index])?;
+                nested.types.iter().find(|t| t.name == short)
+            })
+    }
+
+    /// Like `find_type`, for enums.
+    pub fn find_enum<'b>(&self, schema: &'b SchemaFile, name: &str) -> Option<&'b Enum>
+    where
+        'a: 'b,
+    {
+        let (files, short) = self.get_candidates(schema, name);
+        files
+            .into_iter()
+            .flat_map(|schema| schema.enums.iter())
+            .find(|e| e.name == short)
+            .or_else(|| {
+                let index = name.rfind('.')?;
+                let (_, nested) = self.find_parent(schema, &name[..index])?;
+                nested.enums.iter().find(|e| e.name == short)
+            })
+    }
+
+    pub fn check(&self, schema: &'a SchemaFile) -> Vec<Diagnostic> {
+        let mut errors = self.check_missing(schema);
+        errors.extend(self.check_cycle(schema));
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::ast::Component;
+    use crate::ast::DataType;
+    use crate::ast::Member;
+    use crate::ast::AST;
+    use std::path::PathBuf;
+
+    fn schema(path: &str, imports: &[&str], types: Vec<Type>) -> SchemaFile {
+        SchemaFile {
+            package_name: vec!["test".to_string()],
+            name: "test".to_string(),
+            path: PathBuf::from(path),
+            import_path: PathBuf::from(path),
+            imports: imports.iter().map(|i| i.to_string()).collect(),
+            types,
+            enums: Vec::new(),
+            components: Vec::new(),
+            source: String::new(),
+        }
+    }
+
+    fn user_type(name: &str, member_type: &str) -> Type {
+        Type {
+            name: name.to_string(),
+            span: None,
+            doc: None,
+            annotations: Vec::new(),
+            types: Vec::new(),
+            enums: Vec::new(),
+            members: vec![Member {
+                name: "value".to_string(),
+                doc: None,
+                annotations: Vec::new(),
+                m_type: DataType::UserDefined(member_type.to_string()),
+                id: 1,
+                span: None,
+                boxed: false,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_check_missing_import() {
+        let a = schema("a.schema", &["b.schema"], Vec::new());
+        let graph = ImportGraph::new(vec![&a]);
+        assert_eq!(
+            graph.check(&a),
+            vec![Diagnostic::new(
+                "a.schema",
+                "cannot find imported file \"b.schema\""
+            )]
+        );
+    }
+
+    #[test]
+    fn test_check_circular_import() {
+        let a = schema("a.schema", &["b.schema"], Vec::new());
+        let b = schema("b.schema", &["a.schema"], Vec::new());
+        let graph = ImportGraph::new(vec![&a, &b]);
+        assert_eq!(
+            graph.check(&a),
+            vec![Diagnostic::new(
+                "a.schema",
+                "circular import \"a.schema\" -> \"b.schema\" -> \"a.schema\""
+            )]
+        );
+    }
+
+    #[test]
+    fn test_check_visibility() {
+        let a = schema("a.schema", &[], vec![user_type("A", "B")]);
+        let b = schema("b.schema", &[], vec![user_type("B", "B")]);
+        let c = schema("c.schema", &["b.schema"], vec![user_type("C", "B")]);
+        let graph = ImportGraph::new(vec![&a, &b, &c]);
+        assert_eq!(
+            a.clone().resolve_references(&graph),
+            vec![Diagnostic::new(
+                "a.schema",
+                "`B` is not defined in this file or in any file it imports"
+            )]
+        );
+        assert!(b.clone().resolve_references(&graph).is_empty());
+        assert!(c.clone().resolve_references(&graph).is_empty());
+    }
+
+    #[test]
+    fn test_find_type() {
+        let a = schema("a.schema", &["b.schema"], vec![user_type("A", "B")]);
+        let b = schema("b.schema", &[], vec![user_type("B", "B")]);
+        let c = schema("c.schema", &[], vec![user_type("C", "C")]);
+        let graph = ImportGraph::new(vec![&a, &b, &c]);
+        assert_eq!(graph.find_type(&a, "A").map(|t| &t.name[..]), Some("A"));
+        assert_eq!(graph.find_type(&a, "B").map(|t| &t.name[..]), Some("B"));
+        assert_eq!(graph.find_type(&a, "C"), None);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let a = schema("a.schema", &["b.schema"], vec![user_type("A", "B")]);
+        let b = SchemaFile {
+            package_name: vec!["other".to_string(), "package".to_string()],
+            ..schema("b.schema", &[], vec![user_type("B", "B")])
+        };
+        let graph = ImportGraph::new(vec![&a, &b]);
+        assert_eq!(graph.resolve(&a, "A"), Ok("test.A".to_string()));
+        assert_eq!(
+            graph.resolve(&a, "B"),
+            Err("`B` is defined in package `other.package`, so it has to be qualified as `other.package.B`".to_string())
+        );
+        assert_eq!(
+            graph.resolve(&b, "test.A"),
+            Err("`test.A` is defined in \"a.schema\", which this file does not import".to_string())
+        );
+        assert_eq!(
+            graph.resolve(&a, "other.package.B"),
+            Ok("other.package.B".to_string())
+        );
+        assert!(graph.resolve(&b, "A").is_err());
+        assert!(graph.resolve(&b, "other.B").is_err());
+        assert_eq!(graph.check(&b), Vec::new());
+        let standard_library = AST::default();
+        let graph = ImportGraph::new(standard_library.get_schemas().into_iter().chain(vec![&b]));
+        assert_eq!(
+            graph.resolve(&b, "improbable.Coordinates"),
+            Ok("improbable.Coordinates".to_string())
+        );
+        let c = schema("c.schema", &[], vec![user_type("C", "other.C")]);
+        let graph = ImportGraph::new(vec![&a, &b, &c]);
+        assert_eq!(
+            c.clone().resolve_references(&graph),
+            vec![Diagnostic::new(
+                "c.schema",
+                "`C` is not defined in package `other`"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_resolve_enclosing_packages() {
+        let inner = |path, imports, types| SchemaFile {
+            package_name: vec!["test".to_string(), "inner".to_string()],
+            ..schema(path, imports, types)
+        };
+        let a = schema(
+            "a.schema",
+            &[],
+            vec![user_type("A", "A"), user_type("B", "B")],
+        );
+        let b = inner("b.schema", &[], vec![user_type("B", "B")]);
+        let c = inner(
+            "c.schema",
+            &["a.schema", "b.schema"],
+            vec![user_type("C", "A")],
+        );
+        let graph = ImportGraph::new(vec![&a, &b, &c]);
+        assert_eq!(graph.resolve(&c, "A"), Ok("test.A".to_string()));
+        assert_eq!(graph.resolve(&c, "B"), Ok("test.inner.B".to_string()));
+        assert_eq!(graph.resolve(&c, "test.B"), Ok("test.B".to_string()));
+        let d = schema("d.schema", &["b.schema"], vec![user_type("D", "B")]);
+        let graph = ImportGraph::new(vec![&b, &d]);
+        assert_eq!(
+            graph.resolve(&d, "B"),
+            Err("`B` is defined in package `test.inner`, so it has to be qualified as `test.inner.B`".to_string())
+        );
+        assert_eq!(
+            graph.resolve(&d, "test.inner.B"),
+            Ok("test.inner.B".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_nested() {
+        let mut health = user_type("Health", "Health");
+        health.types.push(Type {
+            types: vec![user_type("Inner", "Inner")],
+            ..user_type("Hit", "Hit")
+        });
+        let a = schema("a.schema", &[], vec![health]);
+        let b = schema("b.schema", &["a.schema"], Vec::new());
+        let graph = ImportGraph::new(vec![&a, &b]);
+        assert_eq!(
+            graph.resolve(&b, "Health.Hit"),
+            Ok("test.Health.Hit".to_string())
+        );
+        assert_eq!(
+            graph.resolve(&b, "test.Health.Hit.Inner"),
+            Ok("test.Health.Hit.Inner".to_string())
+        );
+        assert_eq!(
+            graph.resolve(&b, "Health.Miss"),
+            Err("`Miss` is not defined in `Health`".to_string())
+        );
+        assert!(graph.resolve(&b, "Hit").is_err());
+        assert!(graph.find_type(&b, "test.Health.Hit.Inner").is_some());
+    }
+
+    #[test]
+    fn test_resolve_ambiguous() {
+        let a = schema(
+            "a.schema",
+            &["b.schema", "c.schema"],
+            vec![user_type("A", "B")],
+        );
+        let b = schema("b.schema", &[], vec![user_type("B", "B")]);
+        let c = schema("c.schema", &[], vec![user_type("B", "B")]);
+        let d = schema("d.schema", &["b.schema"], vec![user_type("B", "B")]);
+        let graph = ImportGraph::new(vec![&a, &b, &c, &d]);
+        assert_eq!(
+            graph.resolve(&a, "B"),
+            Err("`B` is ambiguous, as it is defined in \"b.schema\" and \"c.schema\"".to_string())
+        );
+        assert_eq!(graph.resolve(&d, "B"), Ok("test.B".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_modules() {
+        let b = SchemaFile {
+            name: "b".to_string(),
+            ..schema("b.schema", &[], vec![user_type("B", "B")])
+        };
+        let d = SchemaFile {
+            name: "d".to_string(),
+            ..schema("d.schema", &["b.schema"], vec![user_type("B", "B")])
+        };
+        let graph = ImportGraph::new(vec![&b, &d]).with_exports(ExportStrategy::Modules);
+        assert_eq!(graph.resolve(&d, "B"), Ok("test.d.B".to_string()));
+        assert_eq!(graph.resolve(&d, "test.B"), Ok("test.d.B".to_string()));
+        assert_eq!(graph.resolve(&d, "test.b.B"), Ok("test.b.B".to_string()));
+        assert!(graph.find_type(&d, "test.b.B").is_some());
+    }
+
+    #[test]
+    fn test_resolve_component() {
+        let a = SchemaFile {
+            components: vec![Component {
+                name: "Health".to_string(),
+                span: None,
+                doc: None,
+                annotations: Vec::new(),
+                id: 1000,
+                members: Vec::new(),
+                events: Vec::new(),
+                commands: Vec::new(),
+                types: Vec::new(),
+                enums: Vec::new(),
+                data: None,
+            }],
+            ..schema("a.schema", &[], Vec::new())
+        };
+        let graph = ImportGraph::new(vec![&a]);
+        assert_eq!(
+            graph.resolve(&a, "Health"),
+            Err("`Health` is a component, not a type or an enum".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_located() {
+        let source = "package test;\n\ntype A {\n    list<Damage> hits = 1;\n}\n";
+        let (a, errors) = SchemaFile::parse(PathBuf::from("a.schema"), source);
+        assert!(errors.is_empty());
+        let a = a.unwrap();
+        let graph = ImportGraph::new(vec![&a]);
+        let start = source.find("Damage").unwrap();
+        assert_eq!(
+            a.clone().resolve_references(&graph),
+            vec![Diagnostic::new(
+                "a.schema",
+                "`Damage` is not defined in this file or in any file it imports"
+            )
+            .with_span(source, start..start + "Damage".len())]
+        );
+    }
+}
@@ -1,32 +1,133 @@
+use crate::ast::generate_doc;
+use crate::ast::ident;
+use crate::ast::Annotation;
 use crate::ast::DataType;
+use crate::ast::Located;
+use crate::ast::Resolve;
+use crate::ast::SourceSpan;
+use crate::ast::Unresolved;
+use proc_macro2::Literal;
+use proc_macro2::TokenStream;
+use quote::quote;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Member {
     pub name: String,
+    pub doc: Option<String>,
+    pub annotations: Vec<Annotation>,
     pub m_type: DataType,
     pub id: usize,
+    pub span: Option<SourceSpan>,
+    /// Whether the field is generated behind a `Box`, as it would otherwise
+    /// make its type contain itself.
+    pub boxed: bool,
 }
 
 impl Member {
-    pub fn generate_one(&self) -> String {
-        format!("    {}: {}", self.name, self.m_type.rust_type())
-    }
-
-    pub fn generate_multiple(data: &[Self]) -> String {
-        if !data.is_empty() {
-            let members = data
-                .iter()
-                .map(Member::generate_one)
-                .fold(String::new(), |acc, val| {
-                    if !acc.is_empty() {
-                        acc + ",\n" + &val
-                    } else {
-                        val
-                    }
-                });
-            "\n".to_string() + &members + "\n"
-        } else {
-            "".to_string()
+    /// Returns the type of the field, boxed if need be.
+    pub fn rust_type(&self) -> TokenStream {
+        match &self.m_type {
+            DataType::Option(inner) if self.boxed => {
+                let inner = inner.rust_type();
+                quote! { ::std::option::Option<::std::boxed::Box<#inner>> }
+            }
+            m_type if self.boxed => {
+                let m_type = m_type.rust_type();
+                quote! { ::std::boxed::Box<#m_type> }
+            }
+            m_type => m_type.rust_type(),
         }
     }
+
+    /// Generates the field along with its schema id, which the macros use
+    /// to order the fields and track their changes.
+    pub fn generate_one(&self) -> TokenStream {
+        let doc = generate_doc(&self.doc);
+        let annotations = Annotation::generate_multiple(&self.annotations);
+        let id = Literal::usize_unsuffixed(self.id);
+        let name = ident(&self.name);
+        let m_type = self.rust_type();
+        quote! {
+            #doc
+            #annotations
+            #[spatial(field = #id)]
+            #name: #m_type
+        }
+    }
+
+    /// Returns the fields in the order of their ids, the one of the
+    /// parameters of the generated `new`.
+    pub fn sort_by_id(data: &[Self]) -> Vec<Self> {
+        let mut sorted = data.to_vec();
+        sorted.sort_by_key(|member| member.id);
+        sorted
+    }
+
+    pub fn resolve_references(data: &mut [Self], resolve: &Resolve, errors: &mut Vec<Unresolved>) {
+        for member in data {
+            member.m_type = member.m_type.resolve(resolve, member.span, errors);
+        }
+    }
+
+    pub fn check_types(
+        data: &[Self],
+        parent: &str,
+        is_enum: &dyn Fn(&str) -> bool,
+    ) -> Vec<Located> {
+        data.iter()
+            .flat_map(|member| {
+                member.m_type.check(is_enum).into_iter().map(move |error| {
+                    let message = format!("field `{}` of `{}`: {}", member.name, parent, error);
+                    Located::new(message, member.span, &member.name)
+                })
+            })
+            .collect()
+    }
+
+    /// Checks the field ids are non-zero and unique among `data`.
+    pub fn check_ids(data: &[Self], parent: &str) -> Vec<Located> {
+        data.iter()
+            .enumerate()
+            .filter_map(|(index, member)| {
+                if member.id == 0 {
+                    let message = format!(
+                        "field `{}` of `{}` has id 0, while field ids start at 1",
+                        member.name, parent
+                    );
+                    return Some(Located::new(message, member.span, &member.name));
+                }
+                if member.id > u32::MAX as usize {
+                    let message = format!(
+                        "field `{}` of `{}` has id {}, while ids go up to {}",
+                        member.name,
+                        parent,
+                        member.id,
+                        u32::MAX
+                    );
+                    return Some(Located::new(message, member.span, &member.id.to_string()));
+                }
+                data[..index]
+                    .iter()
+                    .find(|other| other.id == member.id)
+                    .map(|other| {
+                        let message = format!(
+                            "field `{}` of `{}` has id {}, already used by `{}`",
+                            member.name, parent, member.id, other.name
+                        );
+                        Located::new(message, member.span, &member.name)
+                    })
+            })
+            .collect()
+    }
+
+    pub fn get_references(data: &[Self]) -> Vec<&str> {
+        data.iter()
+            .flat_map(|member| member.m_type.get_references())
+            .collect()
+    }
+
+    pub fn generate_multiple(data: &[Self]) -> TokenStream {
+        let members = data.iter().map(Member::generate_one);
+        quote! { #(#members),* }
+    }
 }
@@ -1,28 +1,57 @@
 pub mod root;
+pub mod annotation;
 pub mod ast_node;
 pub mod command;
 pub mod component;
 pub mod data_type;
+pub mod doc;
 pub mod r#enum;
 pub mod event;
+pub mod export;
 pub mod header;
+pub mod ident;
+pub mod import;
 pub mod member;
+pub mod module_map;
+pub mod nested;
 pub mod package_node;
+pub mod resolve;
 pub mod schema_file;
+pub mod span;
 pub mod std;
 pub mod r#type;
+pub mod type_graph;
 pub mod value;
 
 pub use root::AST;
+pub use annotation::Annotation;
+pub use annotation::AnnotationValue;
 pub use ast_node::ASTNode;
 pub use command::Command;
 pub use component::Component;
+pub use component::ComponentData;
 pub use data_type::DataType;
+pub use doc::generate_doc;
 pub use event::Event;
+pub use export::ExportStrategy;
 pub use header::Header;
+pub use ident::ident;
+pub use ident::is_identifier;
+pub use ident::pascal_case;
+pub use ident::rust_ident;
+pub use ident::rust_path;
+pub use import::ImportGraph;
 pub use member::Member;
+pub use module_map::ModuleMap;
+pub use nested::Nested;
 pub use package_node::PackageNode;
 pub use r#enum::Enum;
 pub use r#type::Type;
+pub use type_graph::TypeGraph;
+pub use resolve::Resolve;
+pub use resolve::Unresolved;
+pub use schema_file::pretty_print;
 pub use schema_file::SchemaFile;
+pub use span::Located;
+pub use span::SourceSpan;
 pub use value::Value;
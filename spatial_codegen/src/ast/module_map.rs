@@ -0,0 +1,226 @@
+use crate::ast::is_identifier;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// The package of the standard library, which every package it holds
+/// starts with.
+pub const STANDARD_LIBRARY: &str = "improbable";
+
+/// How the packages of the schemas map to the paths of the generated
+/// modules, and where those modules find each other.
+///
+/// ```ignore
+/// let modules = ModuleMap::new()
+///     .with_prefix("io.nebulis", "game")
+///     .with_root("crate::schema")
+///     .with_standard_library("::spatial_std");
+/// ```
+///
+/// By default, modules are named after their package, cross-references go
+/// up to the root through `super`, and the standard library is generated
+/// under `improbable`.
+///
+/// Invalid packages, modules and paths are left out of the map, and
+/// reported by `AST::load_roots` when loading the schemas.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct ModuleMap {
+    /// Package prefixes, with the module path replacing them.
+    prefixes: Vec<(Vec<String>, Vec<String>)>,
+    root: Option<String>,
+    standard_library: Option<String>,
+    errors: Vec<String>,
+}
+
+fn split(path: &str, separator: &str) -> Vec<String> {
+    path.split(separator)
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn check_path(path: &str) -> Result<syn::Path, String> {
+    syn::parse_str::<syn::Path>(path).map_err(|e| format!("`{}` is not a Rust path: {}", path, e))
+}
+
+impl ModuleMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generates the packages starting with `package` into modules starting
+    /// with `module` instead, both dot-separated. An empty `module` strips
+    /// the prefix. When several prefixes match, the longest one applies.
+    pub fn with_prefix(mut self, package: &str, module: &str) -> Self {
+        let (package, module) = (split(package, "."), split(module, "."));
+        if let Some(segment) = package.iter().chain(&module).find(|s| !is_identifier(s)) {
+            self.errors
+                .push(format!("`{}` cannot name a package or a module", segment));
+            return self;
+        }
+        self.prefixes.retain(|(other, _)| other != &package);
+        self.prefixes.push((package, module));
+        self
+    }
+
+    /// Sets the path of the module the generated modules are declared in,
+    /// as in `crate::schema`, for cross-references to start from rather than
+    /// going up through `super`.
+    pub fn with_root(mut self, root: &str) -> Self {
+        match check_path(root) {
+            Ok(_) => self.root = Some(root.to_string()),
+            Err(error) => self.errors.push(error),
+        }
+        self
+    }
+
+    /// Re-exports the standard library from `path`, as in `::spatial_std`,
+    /// rather than generating it, so several crates can share its types.
+    /// `path` is expected to hold the `improbable` module generated there,
+    /// which no prefix applies to.
+    pub fn with_standard_library(mut self, path: &str) -> Self {
+        match check_path(path) {
+            Ok(_) => self.standard_library = Some(path.to_string()),
+            Err(error) => self.errors.push(error),
+        }
+        self
+    }
+
+    /// The packages, modules and paths given to the map which were invalid,
+    /// and so left out of it.
+    pub fn get_errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /// The path the standard library is re-exported from, if it is not
+    /// generated.
+    pub fn get_standard_library(&self) -> Option<&str> {
+        self.standard_library.as_deref()
+    }
+
+    /// Returns the module path `package` is generated into.
+    pub fn map_package(&self, package: &[String]) -> Vec<String> {
+        if self.standard_library.is_some()
+            && package.first().map(String::as_str) == Some(STANDARD_LIBRARY)
+        {
+            return package.to_vec();
+        }
+        self.prefixes
+            .iter()
+            .filter(|(prefix, _)| package.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(prefix, module)| {
+                module
+                    .iter()
+                    .chain(&package[prefix.len()..])
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_else(|| package.to_vec())
+    }
+
+    /// Maps the package of `name`, a package-qualified reference, as
+    /// `map_package` does.
+    pub fn map_reference(&self, name: &str) -> String {
+        let segments = split(name, ".");
+        match segments.split_last() {
+            Some((last, package)) if !package.is_empty() => {
+                let mut mapped = self.map_package(package);
+                mapped.push(last.clone());
+                mapped.join(".")
+            }
+            _ => name.to_string(),
+        }
+    }
+
+    /// The path of the root module, from a file `depth` modules down it.
+    pub fn root(&self, depth: usize) -> TokenStream {
+        match &self.root {
+            Some(root) => {
+                let root = check_path(root).expect("the root is checked by `with_root`");
+                quote!(#root)
+            }
+            None => {
+                let supers = vec![quote!(super); depth];
+                quote!(#(#supers)::*)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn package(name: &str) -> Vec<String> {
+        split(name, ".")
+    }
+
+    #[test]
+    fn test_map_package() {
+        let modules = ModuleMap::new()
+            .with_prefix("io.nebulis", "game")
+            .with_prefix("io.nebulis.player", "")
+            .with_prefix("improbable", "spatial.std");
+        assert_eq!(modules.map_package(&package("io.nebulis")), package("game"));
+        assert_eq!(
+            modules.map_package(&package("io.nebulis.physics")),
+            package("game.physics")
+        );
+        assert_eq!(
+            modules.map_package(&package("io.nebulis.player.health")),
+            package("health")
+        );
+        assert_eq!(
+            modules.map_package(&package("io.other")),
+            package("io.other")
+        );
+        assert_eq!(modules.map_reference("io.nebulis.Mass"), "game.Mass");
+        assert_eq!(
+            modules.map_reference("improbable.Coordinates"),
+            "spatial.std.Coordinates"
+        );
+        assert_eq!(modules.map_reference("Mass"), "Mass");
+        let modules = modules.with_standard_library("::spatial_std");
+        assert_eq!(
+            modules.map_reference("improbable.Coordinates"),
+            "improbable.Coordinates"
+        );
+    }
+
+    #[test]
+    fn test_root() {
+        let modules = ModuleMap::new();
+        assert_eq!(
+            modules.root(2).to_string(),
+            quote!(super::super).to_string()
+        );
+        let modules = modules.with_root("crate::schema");
+        assert_eq!(
+            modules.root(2).to_string(),
+            quote!(crate::schema).to_string()
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let modules = ModuleMap::new()
+            .with_prefix("io-nebulis", "game")
+            .with_prefix("io.nebulis", "game.2d")
+            .with_root("crate::")
+            .with_standard_library("::spatial std");
+        assert_eq!(
+            modules.get_errors(),
+            &[
+                "`io-nebulis` cannot name a package or a module".to_string(),
+                "`2d` cannot name a package or a module".to_string(),
+                "`crate::` is not a Rust path: unexpected end of input, expected identifier"
+                    .to_string(),
+                "`::spatial std` is not a Rust path: unexpected token".to_string(),
+            ]
+        );
+        assert_eq!(modules.map_reference("io.nebulis.Mass"), "io.nebulis.Mass");
+        assert_eq!(modules.get_standard_library(), None);
+        assert_eq!(modules.root(1).to_string(), quote!(super).to_string());
+    }
+}
@@ -0,0 +1,227 @@
+use crate::ast::ident;
+use crate::ast::Enum;
+use crate::ast::Event;
+use crate::ast::Member;
+use crate::ast::Resolve;
+use crate::ast::Type;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::HashSet;
+
+/// The module the definitions nested in `parent` are generated in.
+fn module_name(parent: &str) -> String {
+    parent
+        .chars()
+        .enumerate()
+        .fold(String::new(), |acc, (index, c)| {
+            if c.is_ascii_uppercase() && index > 0 && !acc.ends_with('_') {
+                acc + "_" + &c.to_ascii_lowercase().to_string()
+            } else {
+                acc + &c.to_ascii_lowercase().to_string()
+            }
+        })
+}
+
+/// The types and enums defined inside a type or a component.
+///
+/// They are generated into a module named after their parent, so
+/// `Health`'s `DamageKind` becomes `health::DamageKind`.
+pub struct Nested<'a> {
+    pub parent: &'a str,
+    pub types: &'a [Type],
+    pub enums: &'a [Enum],
+}
+
+impl<'a> Nested<'a> {
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty() && self.enums.is_empty()
+    }
+
+    pub fn module_name(&self) -> String {
+        module_name(self.parent)
+    }
+
+    /// Points `reference`, resolved to a definition nested in a type or a
+    /// component as in `io.nebulis.Health.Kind`, into the modules its
+    /// parents generate it in, as in `io.nebulis.health.Kind`. `scopes` are
+    /// those of the files, which resolved names start with.
+    pub fn generated_path(reference: &str, scopes: &HashSet<String>) -> String {
+        let segments = reference.split('.').collect::<Vec<&str>>();
+        let scope = (1..segments.len())
+            .rev()
+            .find(|len| scopes.contains(&segments[..*len].join(".")));
+        match scope {
+            Some(len) => segments[..len]
+                .iter()
+                .map(|segment| segment.to_string())
+                .chain(
+                    segments[len..segments.len() - 1]
+                        .iter()
+                        .map(|parent| module_name(parent)),
+                )
+                .chain(segments.last().map(|name| name.to_string()))
+                .collect::<Vec<String>>()
+                .join("."),
+            None => reference.to_string(),
+        }
+    }
+
+    pub fn get_names(&self) -> Vec<&'a str> {
+        self.types
+            .iter()
+            .map(|t| t.name.as_str())
+            .chain(self.enums.iter().map(|e| e.name.as_str()))
+            .collect()
+    }
+
+    /// Drops the references to nested definitions, which are in scope
+    /// whatever the file imports.
+    pub fn filter_references<'b>(&self, references: Vec<&'b str>) -> Vec<&'b str> {
+        let names = self.get_names();
+        references
+            .into_iter()
+            .filter(|reference| !names.contains(reference))
+            .collect()
+    }
+
+    /// Wraps `resolve` so it leaves alone the nested definitions, which
+    /// shadow whatever the file imports.
+    pub fn scope<'b>(&self, resolve: &'b Resolve) -> impl Fn(&str) -> Result<String, String> + 'b {
+        let names = self
+            .get_names()
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<String>>();
+        move |name| {
+            if names.iter().any(|n| n == name) {
+                Ok(name.to_string())
+            } else {
+                resolve(name)
+            }
+        }
+    }
+
+    /// Wraps `is_enum` so it knows of the nested definitions, which shadow
+    /// whatever the file imports.
+    pub fn enum_scope<'b>(&self, is_enum: &'b dyn Fn(&str) -> bool) -> impl Fn(&str) -> bool + 'b {
+        let enums = self
+            .enums
+            .iter()
+            .map(|e| e.name.clone())
+            .collect::<Vec<String>>();
+        let names = self
+            .get_names()
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<String>>();
+        move |name| {
+            if names.iter().any(|n| n == name) {
+                enums.iter().any(|n| n == name)
+            } else {
+                is_enum(name)
+            }
+        }
+    }
+
+    /// Points the members' references to nested definitions into the
+    /// nested module, as the members are generated outside of it.
+    pub fn qualify(&self, members: &[Member]) -> Vec<Member> {
+        let names = self.get_names();
+        let module = self.module_name();
+        members
+            .iter()
+            .map(|member| Member {
+                m_type: member.m_type.qualify(&module, &names),
+                ..member.clone()
+            })
+            .collect()
+    }
+
+    /// Points the events' references to nested definitions into the
+    /// nested module, like `qualify` does for members.
+    pub fn qualify_events(&self, events: &[Event]) -> Vec<Event> {
+        let names = self.get_names();
+        let module = self.module_name();
+        events
+            .iter()
+            .map(|event| Event {
+                r_type: event.r_type.qualify(&module, &names),
+                ..event.clone()
+            })
+            .collect()
+    }
+
+    pub fn get_export(&self) -> Option<String> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.module_name())
+        }
+    }
+
+    pub fn generate(&self) -> TokenStream {
+        if self.is_empty() {
+            return TokenStream::new();
+        }
+        let module = ident(&self.module_name());
+        let enums = Enum::generate_multiple(self.enums);
+        let types = Type::generate_multiple(self.types);
+        quote! {
+            pub mod #module {
+                use super::*;
+
+                #enums
+                #types
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::ast::DataType;
+
+    #[test]
+    fn test_qualify() {
+        let enums = vec![Enum {
+            name: "DamageKind".to_string(),
+            span: None,
+            doc: None,
+            annotations: Vec::new(),
+            values: Vec::new(),
+        }];
+        let nested = Nested {
+            parent: "PlayerHealth",
+            types: &[],
+            enums: &enums,
+        };
+        let member = Member {
+            name: "kinds".to_string(),
+            doc: None,
+            annotations: Vec::new(),
+            m_type: DataType::List(Box::new(DataType::UserDefined("DamageKind".to_string()))),
+            id: 1,
+            span: None,
+            boxed: false,
+        };
+        assert_eq!(nested.module_name(), "player_health");
+        assert_eq!(
+            Nested {
+                parent: "Damage_Kind",
+                ..nested
+            }
+            .module_name(),
+            "damage_kind"
+        );
+        assert_eq!(
+            nested.qualify(&[member])[0].m_type.rust_type().to_string(),
+            quote! { ::std::vec::Vec<player_health::DamageKind> }.to_string()
+        );
+        assert_eq!(
+            nested.filter_references(vec!["DamageKind", "Damage"]),
+            vec!["Damage"]
+        );
+    }
+}
@@ -20,8 +20,8 @@ impl PackageNode {
         self.inner
             .iter()
             .map(|node| match &node {
-                ASTNode::SchemaNode(_) => false,
                 ASTNode::PackageNode(pn) => pn.name == *path.as_ref(),
+                _ => false,
             })
             .fold(false, |acc, val| acc | val)
     }
@@ -0,0 +1,14 @@
+use crate::ast::SourceSpan;
+
+/// Gives the package-qualified name of the definition a reference stands
+/// for, or why there is none.
+pub type Resolve<'a> = dyn Fn(&str) -> Result<String, String> + 'a;
+
+/// A reference that could not be resolved.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Unresolved {
+    pub name: String,
+    pub message: String,
+    /// The node the reference was made from.
+    pub span: Option<SourceSpan>,
+}
@@ -1,6 +1,18 @@
+use crate::ast::export::check_exports;
+use crate::ast::module_map::STANDARD_LIBRARY;
 use crate::ast::std::generate_standard_library;
 use crate::ast::ASTNode;
+use crate::ast::ExportStrategy;
+use crate::ast::ImportGraph;
+use crate::ast::ModuleMap;
+use crate::ast::Nested;
 use crate::ast::SchemaFile;
+use crate::ast::TypeGraph;
+use crate::builder::write_if_changed;
+use crate::diagnostic::CodegenError;
+use crate::diagnostic::Diagnostic;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::ffi::OsStr;
 use std::path::Path;
@@ -10,6 +22,8 @@ use walkdir::WalkDir;
 #[derive(Debug, Eq, PartialEq)]
 pub struct AST {
     pub inner: Vec<ASTNode>,
+    pub exports: ExportStrategy,
+    pub modules: ModuleMap,
 }
 
 impl Default for AST {
@@ -19,72 +33,724 @@ impl Default for AST {
 }
 
 impl AST {
+    /// Generates the modules into `path`, replacing whatever it holds. Build
+    /// scripts should prefer `Builder`, which leaves unchanged files alone.
     pub fn generate<P: AsRef<Path> + Clone>(&self, path: P) -> Result<(), std::io::Error> {
-        let path_clone = path.clone();
-        if path_clone.as_ref().exists() {
-            std::fs::remove_dir_all(path)?;
+        if path.as_ref().exists() {
+            std::fs::remove_dir_all(path.clone())?;
         }
+        for (file, contents) in self.render() {
+            let file = path.as_ref().join(file);
+            if let Some(parent) = file.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(file, contents)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the files of the generated modules, with their paths
+    /// relative to the directory they are generated in, `mod.rs` last.
+    pub fn render(&self) -> Vec<(PathBuf, String)> {
+        let mut files = Vec::new();
         for node in &self.inner {
-            node.generate_node(path_clone.clone())?;
+            node.render_node(Path::new(""), self.exports, &self.modules, &mut files);
         }
-        ASTNode::generate_mod_rs(&self.inner, path_clone)
+        files.push((
+            PathBuf::from("mod.rs"),
+            ASTNode::render_mod_rs(&self.inner, self.exports, &self.modules, None),
+        ));
+        files
     }
 
-    fn merge_schema<T: AsRef<str>>(self, schema: &SchemaFile, path: &[T]) -> Self {
-        if !path.is_empty() {
-            let is_path_present = self
-                .inner
-                .iter()
-                .map(|n| match n {
-                    ASTNode::SchemaNode(_) => panic!("SchemaFile shouldn't be at the root of AST"),
-                    ASTNode::PackageNode(pn) => pn.name == *path[0].as_ref(),
-                })
-                .fold(false, |acc, val| acc | val);
-            if is_path_present {
-                AST {
-                    inner: self
-                        .inner
-                        .into_iter()
-                        .map(|n| n.merge_schema(schema, path))
-                        .collect::<Vec<ASTNode>>(),
+    /// Renders a file declaring the generated modules, for `include!`, when
+    /// they are generated in `dir`, an absolute path.
+    pub fn render_entry(&self, dir: &Path) -> String {
+        ASTNode::render_mod_rs(&self.inner, self.exports, &self.modules, Some(dir))
+    }
+
+    /// Generates the modules into the single file `path`, as nested inline
+    /// modules with the same paths as the ones `generate` writes. The file
+    /// is left alone when it is up to date, so Cargo does not rebuild what
+    /// includes it.
+    pub fn generate_file<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
+        write_if_changed(path.as_ref(), &self.render_file()).map(|_| ())
+    }
+
+    /// Renders the generated modules, with all their contents, as a single
+    /// file to `include!` or declare as a module.
+    pub fn render_file(&self) -> String {
+        ASTNode::render_inline(&self.inner, self.exports, &self.modules)
+    }
+
+    pub fn get_schemas(&self) -> Vec<&SchemaFile> {
+        self.inner.iter().flat_map(ASTNode::get_schemas).collect()
+    }
+
+    fn merge_schema<T: AsRef<str>>(
+        self,
+        schema: &SchemaFile,
+        path: &[T],
+    ) -> Result<Self, CodegenError> {
+        let package = path
+            .first()
+            .ok_or_else(|| Diagnostic::new(&schema.path, "the file declares no package"))?;
+        let mut is_path_present = false;
+        for node in &self.inner {
+            match node {
+                ASTNode::SchemaNode(other) => {
+                    return Err(Diagnostic::new(
+                        &schema.path,
+                        format!(
+                            "cannot add the file to a tree with \"{}\" at its root",
+                            other.path.display()
+                        ),
+                    )
+                    .into())
                 }
-            } else {
-                let mut inner = self.inner;
-                inner.push(ASTNode::package_schema(schema, path));
-                AST { inner }
+                ASTNode::PackageNode(pn) => is_path_present |= pn.name == package.as_ref(),
+                ASTNode::ExternalModule { name, path } if name == package.as_ref() => {
+                    return Err(Diagnostic::new(
+                        &schema.path,
+                        format!(
+                            "cannot add the file to package `{}`, which is re-exported from `{}`",
+                            name, path
+                        ),
+                    )
+                    .into())
+                }
+                ASTNode::ExternalModule { .. } => {}
             }
+        }
+        if is_path_present {
+            Ok(AST {
+                inner: self
+                    .inner
+                    .into_iter()
+                    .map(|n| n.merge_schema(schema, path))
+                    .collect::<Vec<ASTNode>>(),
+                ..self
+            })
         } else {
-            panic!("SchemaFile does not have a package name");
+            let mut inner = self.inner;
+            inner.push(ASTNode::package_schema(schema, path));
+            Ok(AST { inner, ..self })
         }
     }
 }
 
-impl<P: AsRef<Path>> From<P> for AST {
-    fn from(path: P) -> Self {
-        WalkDir::new(path)
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .map(|e| {
-                e.path()
-                    .to_str()
-                    .map(|s| s.to_string())
-                    .ok_or("Can't tranform into &str")
-            })
-            .filter_map(Result::ok)
-            .map(PathBuf::from)
-            .filter(|p| p.extension() == Some(OsStr::new("schema")))
-            .map(SchemaFile::try_from)
-            .map(|schemas| match schemas {
-                Ok(data) => Ok(data),
-                Err(e) => {
-                    eprintln!("{}", e);
-                    Err(())
+/// Reports the components given an id already used by a component of
+/// `schemas` found before them.
+fn check_component_ids<'a, I: IntoIterator<Item = &'a SchemaFile>>(schemas: I) -> Vec<Diagnostic> {
+    let mut owners: HashMap<usize, (&str, &Path)> = HashMap::new();
+    let mut errors = Vec::new();
+    for schema in schemas {
+        for component in &schema.components {
+            match owners.get(&component.id) {
+                Some((name, path)) => errors.push(Diagnostic::new(
+                    &schema.path,
+                    format!(
+                        "component `{}` has id {}, already used by `{}` in \"{}\"",
+                        component.name,
+                        component.id,
+                        name,
+                        path.display()
+                    ),
+                )),
+                None => {
+                    owners.insert(component.id, (&component.name, &schema.path));
                 }
+            }
+        }
+    }
+    errors
+}
+
+impl AST {
+    /// Loads the schema files found under `path`, along with the standard
+    /// library.
+    ///
+    /// Every file is checked even once one fails, so the error holds all
+    /// the diagnostics found, from I/O failures to the checks made across
+    /// files.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CodegenError> {
+        Self::load_with(path, ExportStrategy::default())
+    }
+
+    /// Like `load`, with the definitions of each package exported as
+    /// `exports` tells.
+    pub fn load_with<P: AsRef<Path>>(
+        path: P,
+        exports: ExportStrategy,
+    ) -> Result<Self, CodegenError> {
+        Self::load_roots(&[path], exports, ModuleMap::default())
+    }
+
+    /// Like `load_with`, for the schema files found under each of `roots`,
+    /// with their packages generated into the modules `modules` tells. Files
+    /// import each other by their path relative to their root.
+    pub fn load_roots<P: AsRef<Path>>(
+        roots: &[P],
+        exports: ExportStrategy,
+        modules: ModuleMap,
+    ) -> Result<Self, CodegenError> {
+        // The module map is set by the build script.
+        let mut diagnostics = modules
+            .get_errors()
+            .iter()
+            .map(|error| Diagnostic::new("build.rs", error))
+            .collect::<Vec<Diagnostic>>();
+        let mut schemas = Vec::new();
+        for root in roots {
+            let root = root.as_ref();
+            let paths = WalkDir::new(root)
+                .follow_links(true)
+                .sort_by(|a, b| a.file_name().cmp(b.file_name()))
+                .into_iter()
+                .filter_map(|entry| match entry {
+                    Ok(entry) => Some(entry.into_path()),
+                    Err(e) => {
+                        let path = e.path().unwrap_or(root).to_path_buf();
+                        diagnostics.push(Diagnostic::new(
+                            path,
+                            format!("Unable to read directory: {}", e),
+                        ));
+                        None
+                    }
+                })
+                .filter(|p| p.extension() == Some(OsStr::new("schema")))
+                .collect::<Vec<PathBuf>>();
+            schemas.extend(
+                paths
+                    .into_iter()
+                    .filter_map(|path| match SchemaFile::try_from(path) {
+                        Ok(schema) => Some(schema),
+                        Err(errors) => {
+                            diagnostics.extend(errors);
+                            None
+                        }
+                    })
+                    .map(|schema| {
+                        let import_path = schema
+                            .path
+                            .strip_prefix(root)
+                            .map(Path::to_path_buf)
+                            .unwrap_or_else(|_| schema.path.clone());
+                        SchemaFile {
+                            import_path,
+                            ..schema
+                        }
+                    }),
+            );
+        }
+        let standard_library = Self::default();
+        let schemas = {
+            let imports = ImportGraph::new(
+                standard_library
+                    .get_schemas()
+                    .into_iter()
+                    .chain(schemas.iter()),
+            )
+            .with_exports(exports);
+            let mut id_errors = check_component_ids(
+                standard_library
+                    .get_schemas()
+                    .into_iter()
+                    .chain(schemas.iter()),
+            );
+            // Names clash in the modules the packages are mapped to.
+            let mapped = standard_library
+                .get_schemas()
+                .into_iter()
+                .chain(schemas.iter())
+                .map(|schema| SchemaFile {
+                    package_name: modules.map_package(&schema.package_name),
+                    ..schema.clone()
+                })
+                .collect::<Vec<SchemaFile>>();
+            id_errors.extend(check_exports(&mapped, exports));
+            id_errors.extend(
+                mapped[standard_library.get_schemas().len()..]
+                    .iter()
+                    .filter(|schema| schema.package_name.is_empty())
+                    .map(|schema| {
+                        Diagnostic::new(
+                            &schema.path,
+                            "the package of the file is mapped to the root of the generated modules, which cannot hold files",
+                        )
+                    }),
+            );
+            schemas
+                .iter()
+                .filter_map(|schema| {
+                    let mut resolved = schema.clone();
+                    let mut errors = imports.check(schema);
+                    errors.extend(schema.check_ids());
+                    errors.extend(
+                        id_errors
+                            .iter()
+                            .filter(|error| error.path == schema.path)
+                            .cloned(),
+                    );
+                    // The checks are independent, so each file reports all of
+                    // its problems at once.
+                    errors.extend(resolved.resolve_references(&imports));
+                    errors.extend(resolved.check_types(&imports));
+                    let valid = errors.is_empty();
+                    diagnostics.extend(errors);
+                    Some(resolved).filter(|_| valid)
+                })
+                .collect::<Vec<SchemaFile>>()
+        };
+        // Fields are boxed before the data types give them to components, so
+        // both agree on their types.
+        let schemas = {
+            let (boxed, errors) = TypeGraph::new(
+                standard_library
+                    .get_schemas()
+                    .into_iter()
+                    .chain(schemas.iter()),
+                exports,
+            )
+            .find_boxed();
+            let schemas = schemas
+                .into_iter()
+                .filter(|schema| errors.iter().all(|error| error.path != schema.path))
+                .map(|mut schema| {
+                    schema.box_fields(&boxed, exports);
+                    schema
+                })
+                .collect::<Vec<SchemaFile>>();
+            diagnostics.extend(errors);
+            schemas
+        };
+        // Data types are looked up once resolved, so the fields they give
+        // refer to the same definitions from any file.
+        let schemas = {
+            let imports = ImportGraph::new(
+                standard_library
+                    .get_schemas()
+                    .into_iter()
+                    .chain(schemas.iter()),
+            )
+            .with_exports(exports);
+            schemas
+                .iter()
+                .filter_map(|schema| {
+                    let mut expanded = schema.clone();
+                    let errors = expanded.expand_data(&imports);
+                    let valid = errors.is_empty();
+                    diagnostics.extend(errors);
+                    Some(expanded).filter(|_| valid)
+                })
+                .collect::<Vec<SchemaFile>>()
+        };
+        if !diagnostics.is_empty() {
+            return Err(CodegenError { diagnostics });
+        }
+        // References name packages as schemas do until every file is in
+        // place, so both are mapped last.
+        let (inner, standard_library) = match modules.get_standard_library() {
+            Some(path) => (
+                vec![ASTNode::ExternalModule {
+                    name: STANDARD_LIBRARY.to_string(),
+                    path: path.to_string(),
+                }],
+                Vec::new(),
+            ),
+            None => (Vec::new(), standard_library.get_schemas()),
+        };
+        let scopes = standard_library
+            .iter()
+            .copied()
+            .chain(schemas.iter())
+            .map(|schema| schema.scope(exports))
+            .collect::<HashSet<String>>();
+        let schemas = standard_library
+            .into_iter()
+            .chain(schemas.iter())
+            .map(|schema| {
+                let mut schema = schema.clone();
+                schema.rename_references(&|name| {
+                    modules.map_reference(&Nested::generated_path(name, &scopes))
+                });
+                schema.package_name = modules.map_package(&schema.package_name);
+                schema
             })
-            .filter_map(Result::ok)
-            .fold(Self::default(), |acc, val| {
-                acc.merge_schema(&val, &val.package_name)
+            .collect::<Vec<SchemaFile>>();
+        let ast = AST {
+            inner,
+            exports,
+            modules,
+        };
+        schemas
+            .iter()
+            .try_fold(ast, |acc, val| acc.merge_schema(val, &val.package_name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_check_component_ids() {
+        let source = "package test;\n\
+                      component Mass { id = 1000; }\n\
+                      component Place { id = 54; }\n\
+                      component Weight { id = 1000; }\n";
+        let (schema, _) = SchemaFile::parse(PathBuf::from("a.schema"), source);
+        let schema = schema.unwrap();
+        let standard_library = AST::default();
+        let schemas = standard_library.get_schemas();
+        assert_eq!(
+            check_component_ids(schemas.iter().copied().chain(std::iter::once(&schema))),
+            vec![
+                Diagnostic::new(
+                    "a.schema",
+                    "component `Place` has id 54, already used by `Position` in \"improbable/standard_library.schema\""
+                ),
+                Diagnostic::new(
+                    "a.schema",
+                    "component `Weight` has id 1000, already used by `Mass` in \"a.schema\""
+                ),
+            ]
+        );
+        assert_eq!(check_component_ids(schemas), Vec::new());
+    }
+
+    #[test]
+    fn test_load_collects_errors() {
+        let root = std::env::temp_dir().join("spatial_codegen_test_load");
+        if root.exists() {
+            std::fs::remove_dir_all(&root).unwrap();
+        }
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("a.schema"),
+            "package test;\nimport \"missing.schema\";\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("b.schema"),
+            "package test;\ntype B { uint32 = 1; }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("c.schema"),
+            "package test;\ntype C { uint32 c = 1; }\n",
+        )
+        .unwrap();
+        let error = AST::load(&root).unwrap_err();
+        std::fs::remove_dir_all(&root).unwrap();
+        // Semantic errors point to the file read, as parse errors do.
+        let mut paths = error
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                diagnostic
+                    .path
+                    .strip_prefix(&root)
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
             })
+            .collect::<Vec<&str>>();
+        paths.sort();
+        assert_eq!(paths, vec!["a.schema", "b.schema"]);
+        assert!(error
+            .to_string()
+            .ends_with("could not load the schemas due to 2 previous errors"));
+    }
+
+    #[test]
+    fn test_load_reports_every_check() {
+        let root = std::env::temp_dir().join("spatial_codegen_test_every_check");
+        if root.exists() {
+            std::fs::remove_dir_all(&root).unwrap();
+        }
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("a.schema"),
+            "package test;\nimport \"missing.schema\";\ntype A { uint32 a = 0; Hit hit = 2; }\n",
+        )
+        .unwrap();
+        let error = AST::load(&root).unwrap_err();
+        std::fs::remove_dir_all(&root).unwrap();
+        let mut messages = error
+            .diagnostics
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect::<Vec<String>>();
+        messages.sort();
+        assert_eq!(
+            messages,
+            vec![
+                "`Hit` is not defined in this file or in any file it imports",
+                "cannot find imported file \"missing.schema\"",
+                "field `a` of `A` has id 0, while field ids start at 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_missing_directory() {
+        let root = std::env::temp_dir().join("spatial_codegen_test_missing");
+        let error = AST::load(&root).unwrap_err();
+        assert_eq!(error.diagnostics.len(), 1);
+        assert_eq!(error.diagnostics[0].path, root);
+    }
+
+    #[test]
+    fn test_merge_schema_errors() {
+        let (schema, _) = SchemaFile::parse(PathBuf::from("a.schema"), "package test;\n");
+        let schema = schema.unwrap();
+        assert_eq!(
+            AST::default().merge_schema::<String>(&schema, &[]),
+            Err(Diagnostic::new("a.schema", "the file declares no package").into())
+        );
+        let ast = AST {
+            inner: vec![ASTNode::SchemaNode(schema.clone())],
+            exports: ExportStrategy::Flatten,
+            modules: ModuleMap::default(),
+        };
+        assert_eq!(
+            ast.merge_schema(&schema, &schema.package_name),
+            Err(Diagnostic::new(
+                "a.schema",
+                "cannot add the file to a tree with \"a.schema\" at its root"
+            )
+            .into())
+        );
+    }
+
+    #[test]
+    fn test_load_with_modules() {
+        let root = std::env::temp_dir().join("spatial_codegen_test_modules");
+        if root.exists() {
+            std::fs::remove_dir_all(&root).unwrap();
+        }
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("a.schema"),
+            "package test;\ntype Damage {}\ntype Strike { Damage damage = 1; }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("b.schema"),
+            "package test;\nimport \"a.schema\";\ntype Damage {}\ntype Hit { test.Damage damage = 1; }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("c.schema"),
+            "package test;\nimport \"b.schema\";\ncomponent Health { id = 1000; data Hit; }\n",
+        )
+        .unwrap();
+        let flattened = AST::load(&root).unwrap_err();
+        assert!(flattened
+            .diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.message.starts_with("type `Damage` clashes")));
+        let ast = AST::load_with(&root, ExportStrategy::Modules).unwrap();
+        let generated = root.join("generated");
+        ast.generate(&generated).unwrap();
+        let module = std::fs::read_to_string(generated.join("test/mod.rs")).unwrap();
+        let a = std::fs::read_to_string(generated.join("test/a.rs")).unwrap();
+        let b = std::fs::read_to_string(generated.join("test/b.rs")).unwrap();
+        let c = std::fs::read_to_string(generated.join("test/c.rs")).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            module,
+            "#![allow(deprecated, non_camel_case_types, non_snake_case)]\npub mod a;\npub mod b;\npub mod c;\n"
+        );
+        // Each reference is generated into the file it was resolved to, as
+        // are the fields a component takes from a data type of another file.
+        assert!(a.contains("damage: schema_root::test::a::Damage"));
+        assert!(b.contains("damage: schema_root::test::b::Damage"));
+        assert!(c.contains("damage: schema_root::test::b::Damage"));
+    }
+
+    #[test]
+    fn test_generate_file_if_changed() {
+        let root = std::env::temp_dir().join("spatial_codegen_test_generate_file");
+        if root.exists() {
+            std::fs::remove_dir_all(&root).unwrap();
+        }
+        let path = root.join("standard_library.rs");
+        let ast = AST::default();
+        ast.generate_file(&path).unwrap();
+        let old = std::time::SystemTime::UNIX_EPOCH;
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        ast.generate_file(&path).unwrap();
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(modified, old);
+        assert_eq!(contents, ast.render_file());
+    }
+
+    #[test]
+    fn test_render_is_deterministic() {
+        let files = [
+            (
+                "a.schema",
+                "package test;\nimport \"b.schema\";\ntype A { test.B b = 1; }\n",
+            ),
+            (
+                "b.schema",
+                "package test;\ntype B { improbable.Coordinates at = 1; }\n",
+            ),
+            (
+                "c.schema",
+                "package other;\nimport \"a.schema\";\ntype C { test.A a = 1; }\n",
+            ),
+        ];
+        let render = |name: &str, files: &[(&str, &str)]| {
+            let root = std::env::temp_dir().join(name);
+            if root.exists() {
+                std::fs::remove_dir_all(&root).unwrap();
+            }
+            std::fs::create_dir_all(&root).unwrap();
+            for (file, source) in files {
+                std::fs::write(root.join(file), source).unwrap();
+            }
+            let ast = AST::load(&root).unwrap();
+            std::fs::remove_dir_all(&root).unwrap();
+            (ast.render(), ast.render_file())
+        };
+        // Files written in another order are read in another order by
+        // some file systems.
+        let mut reversed = files.to_vec();
+        reversed.reverse();
+        let rendered = render("spatial_codegen_test_deterministic", &files);
+        assert_eq!(
+            render("spatial_codegen_test_deterministic_reversed", &reversed),
+            rendered
+        );
+        assert_eq!(
+            render("spatial_codegen_test_deterministic", &files),
+            rendered
+        );
+    }
+
+    #[test]
+    fn test_load_nested_references() {
+        let root = std::env::temp_dir().join("spatial_codegen_test_nested_references");
+        if root.exists() {
+            std::fs::remove_dir_all(&root).unwrap();
+        }
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("a.schema"),
+            "package test.player;
+\
+             component Health {
+\
+                 id = 1000;
+\
+                 enum Kind { FIRE = 0; }
+\
+                 type Hit { Kind kind = 1; }
+\
+             }
+\
+             type Log { Health.Kind kind = 1; option<Health.Hit> last = 2; }
+",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("b.schema"),
+            "package test;
+\
+             import \"a.schema\";
+\
+             type Strike { map<test.player.Health.Kind, test.player.Health.Hit> hits = 1; }
+",
+        )
+        .unwrap();
+        let ast = AST::load(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+        let rendered = ast.unwrap().render_file();
+        assert!(rendered.contains("kind: schema_root::test::player::health::Kind,"));
+        assert!(rendered
+            .contains("last: ::std::option::Option<schema_root::test::player::health::Hit>,"));
+        assert!(rendered.contains("schema_root::test::player::health::Kind,\n                schema_root::test::player::health::Hit,\n"));
+    }
+
+    #[test]
+    fn test_load_mapped() {
+        let root = std::env::temp_dir().join("spatial_codegen_test_mapped");
+        if root.exists() {
+            std::fs::remove_dir_all(&root).unwrap();
+        }
+        std::fs::create_dir_all(root.join("player")).unwrap();
+        std::fs::write(
+            root.join("base.schema"),
+            "package io.nebulis;\ntype Marker { improbable.Coordinates at = 1; }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("player/health.schema"),
+            "package io.nebulis.player;\nimport \"base.schema\";\ntype Hit { io.nebulis.Marker marker = 1; }\n",
+        )
+        .unwrap();
+        let modules = ModuleMap::new()
+            .with_prefix("io.nebulis", "game")
+            .with_root("crate::schema")
+            .with_standard_library("::spatial_std");
+        let ast = AST::load_roots(&[&root], ExportStrategy::Flatten, modules.clone()).unwrap();
+        let stripped = AST::load_roots(
+            &[&root],
+            ExportStrategy::Flatten,
+            modules.with_prefix("io.nebulis", ""),
+        )
+        .unwrap_err();
+        let invalid = AST::load_roots(
+            &[&root],
+            ExportStrategy::Flatten,
+            ModuleMap::new().with_standard_library("::spatial std"),
+        )
+        .unwrap_err();
+        std::fs::remove_dir_all(&root).unwrap();
+        let files = ast
+            .render()
+            .into_iter()
+            .collect::<HashMap<PathBuf, String>>();
+        let mut paths = files.keys().cloned().collect::<Vec<PathBuf>>();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("game/base.rs"),
+                PathBuf::from("game/mod.rs"),
+                PathBuf::from("game/player/health.rs"),
+                PathBuf::from("game/player/mod.rs"),
+                PathBuf::from("mod.rs"),
+            ]
+        );
+        assert_eq!(
+            files[&PathBuf::from("mod.rs")],
+            "#![allow(deprecated, non_camel_case_types, non_snake_case)]\npub use ::spatial_std::improbable;\npub mod game;\n"
+        );
+        let base = &files[&PathBuf::from("game/base.rs")];
+        assert!(base.contains("use crate::schema as schema_root;"));
+        assert!(base.contains("at: schema_root::improbable::Coordinates,"));
+        let health = &files[&PathBuf::from("game/player/health.rs")];
+        assert!(health.contains("marker: schema_root::game::Marker,"));
+        assert_eq!(stripped.diagnostics.len(), 1);
+        assert_eq!(stripped.diagnostics[0].path, root.join("base.schema"));
+        assert_eq!(
+            invalid.diagnostics,
+            vec![Diagnostic::new(
+                "build.rs",
+                "`::spatial std` is not a Rust path: unexpected token"
+            )]
+        );
     }
 }
@@ -1,8 +1,19 @@
-use crate::ast::header::Header;
+use crate::ast::is_identifier;
+use crate::ast::type_graph::Field;
 use crate::ast::Component;
 use crate::ast::Enum;
+use crate::ast::ExportStrategy;
+use crate::ast::Header;
+use crate::ast::ImportGraph;
+use crate::ast::ModuleMap;
+use crate::ast::SourceSpan;
 use crate::ast::Type;
+use crate::diagnostic::Diagnostic;
+use crate::parser::error::SyntaxError;
 use crate::parser::schema_file::parse_schema;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::fs::File;
 use std::io::Read;
@@ -14,22 +25,106 @@ use std::path::PathBuf;
 pub struct SchemaFile {
     pub package_name: Vec<String>,
     pub name: String,
+    /// Where the file was read from, which diagnostics point to.
+    pub path: PathBuf,
+    /// The path other files import this one by, relative to its schema
+    /// root.
+    pub import_path: PathBuf,
+    pub imports: Vec<String>,
     pub types: Vec<Type>,
     pub enums: Vec<Enum>,
     pub components: Vec<Component>,
+    /// The text the file was parsed from, which diagnostics point into.
+    /// Empty for the standard library.
+    pub source: String,
 }
 
 impl SchemaFile {
-    fn generate(&self) -> String {
-        format!(
-            "{}\n{}\n{}\n{}\n",
-            Header::generate(),
-            Enum::generate_multiple(&self.enums),
-            Type::generate_multiple(&self.types),
-            Component::generate_multiple(&self.components)
+    pub fn generate(&self, modules: &ModuleMap) -> TokenStream {
+        let header = Header::generate(modules.root(self.package_name.len() + 1));
+        let enums = Enum::generate_multiple(&self.enums);
+        let types = Type::generate_multiple(&self.types);
+        let components = Component::generate_multiple(&self.components);
+        quote! {
+            #header
+            #enums
+            #types
+            #components
+        }
+    }
+
+    /// Parses `contents`, carrying on past malformed statements.
+    ///
+    /// Every diagnostic found on the way is returned along with whatever
+    /// could be parsed, so tools can work on a file that does not compile
+    /// yet. There is no schema at all when the package could not be read.
+    ///
+    /// The file is generated into a module named after it, so its name has
+    /// to be an identifier. The package needs no such check, as the parser
+    /// only reads identifiers.
+    pub fn parse(path: PathBuf, contents: &str) -> (Option<Self>, Vec<Diagnostic>) {
+        let filename = match path.file_stem().and_then(|s| s.to_str()) {
+            Some(filename) => filename.to_string(),
+            None => {
+                return (
+                    None,
+                    vec![Diagnostic::new(&path, "Unable to get a UTF-8 file stem")],
+                )
+            }
+        };
+        let mut diagnostics = Vec::new();
+        if !is_identifier(&filename) {
+            diagnostics.push(Diagnostic::new(
+                &path,
+                format!(
+                    "the file is generated into a module named `{}`, which is not an identifier: file names take letters, digits and underscores, and do not start with a digit",
+                    filename
+                ),
+            ));
+        } else if filename == "mod" {
+            diagnostics.push(Diagnostic::new(
+                &path,
+                "the file is generated into `mod.rs`, which declares the modules of its package",
+            ));
+        }
+        let mut builder = match parse_schema(contents.as_bytes()) {
+            Ok((_, builder)) => builder,
+            Err(e) => {
+                diagnostics.push(SyntaxError::from_nom(e).to_diagnostic(&path, contents));
+                return (None, diagnostics);
+            }
+        };
+        builder
+            .errors
+            .sort_by_key(|e| std::cmp::Reverse(e.remaining));
+        diagnostics.extend(
+            builder
+                .errors
+                .iter()
+                .map(|e| e.to_diagnostic(&path, contents)),
+        );
+        (
+            builder
+                .with_name(filename)
+                .with_path(path)
+                .with_source(contents.to_string())
+                .build()
+                .ok(),
+            diagnostics,
         )
     }
 
+    /// The prefix of the names resolved to the top-level definitions of the
+    /// file: its package, then, when `exports` generates each file into a
+    /// module of its own, the file, as in `io.nebulis.physics`.
+    pub fn scope(&self, exports: ExportStrategy) -> String {
+        let mut scope = self.package_name.clone();
+        if exports == ExportStrategy::Modules {
+            scope.push(self.name.clone());
+        }
+        scope.join(".")
+    }
+
     pub fn get_exports(&self) -> Vec<String> {
         let mut exports = vec![];
         exports.extend(Enum::get_exports(&self.enums));
@@ -38,33 +133,496 @@ impl SchemaFile {
         exports
     }
 
+    pub fn get_references(&self) -> Vec<&str> {
+        let mut references = vec![];
+        references.extend(Type::get_references(&self.types));
+        references.extend(Component::get_references(&self.components));
+        references
+    }
+
+    /// Checks the collection types used by the fields against what
+    /// schemalang allows.
+    pub fn check_types(&self, imports: &ImportGraph) -> Vec<Diagnostic> {
+        let is_enum = |name: &str| {
+            imports
+                .resolve(self, name)
+                .ok()
+                .and_then(|name| imports.find_enum(self, &name))
+                .is_some()
+        };
+        let mut errors = Type::check_types(&self.types, &is_enum);
+        errors.extend(Component::check_types(&self.components, &is_enum));
+        errors
+            .into_iter()
+            .map(|error| self.locate(error.message, error.span, &error.text))
+            .collect()
+    }
+
+    /// Checks the ids given to fields, enum values and components, apart
+    /// from the ones components share with other files.
+    pub fn check_ids(&self) -> Vec<Diagnostic> {
+        let mut errors = Enum::check_values(&self.enums);
+        errors.extend(Type::check_ids(&self.types));
+        errors.extend(Component::check_ids(&self.components));
+        errors
+            .into_iter()
+            .map(|error| self.locate(error.message, error.span, &error.text))
+            .collect()
+    }
+
+    /// Reports `message` at the first occurrence of `text` within `span`.
+    pub(crate) fn locate(
+        &self,
+        message: String,
+        span: Option<SourceSpan>,
+        text: &str,
+    ) -> Diagnostic {
+        let diagnostic = Diagnostic::new(&self.path, message);
+        match span {
+            Some(span) => diagnostic.with_span(&self.source, span.find(&self.source, text)),
+            None => diagnostic,
+        }
+    }
+
+    /// Rewrites the references to top-level definitions, this file's
+    /// included, into package-qualified names, so they are generated as
+    /// paths from the root module, and reports those that link to no type
+    /// or enum.
+    pub fn resolve_references(&mut self, imports: &ImportGraph) -> Vec<Diagnostic> {
+        let schema = self.clone();
+        let resolve = |name: &str| imports.resolve(&schema, name);
+        let mut errors = Vec::new();
+        Type::resolve_references(&mut self.types, &resolve, &mut errors);
+        Component::resolve_references(&mut self.components, &resolve, &mut errors);
+        errors
+            .into_iter()
+            .map(|error| self.locate(error.message, error.span, &error.name))
+            .collect()
+    }
+
+    /// Replaces the references to top-level definitions with the names
+    /// `rename` gives them.
+    pub fn rename_references(&mut self, rename: &dyn Fn(&str) -> String) {
+        let resolve = |name: &str| Ok(rename(name));
+        let mut errors = Vec::new();
+        Type::resolve_references(&mut self.types, &resolve, &mut errors);
+        Component::resolve_references(&mut self.components, &resolve, &mut errors);
+    }
+
+    /// Boxes the fields `TypeGraph::find_boxed` lists, for the graph built
+    /// with `exports`.
+    pub fn box_fields(&mut self, boxed: &HashSet<Field>, exports: ExportStrategy) {
+        let scope = self.scope(exports);
+        Type::box_fields(&mut self.types, &scope, boxed);
+        Component::box_fields(&mut self.components, &scope, boxed);
+    }
+
+    /// Gives the components declaring `data` the fields of their data type,
+    /// looked up in the component itself first, then through `imports`.
+    pub fn expand_data(&mut self, imports: &ImportGraph) -> Vec<Diagnostic> {
+        let mut errors = Vec::new();
+        let schema = self.clone();
+        for component in &mut self.components {
+            let type_name = match &component.data {
+                Some(data) => data.type_name.clone(),
+                None => continue,
+            };
+            let data_type = component
+                .types
+                .iter()
+                .find(|t| t.name == type_name)
+                .or_else(|| imports.find_type(&schema, &type_name))
+                .cloned();
+            match data_type {
+                Some(data_type) => errors.extend(
+                    component
+                        .expand_data(&data_type)
+                        .into_iter()
+                        .map(|conflict| {
+                            schema.locate(conflict.message, conflict.span, &conflict.text)
+                        }),
+                ),
+                None => errors.push(schema.locate(
+                    format!(
+                        "component `{}` takes its data from `{}`, which is not a type",
+                        component.name, type_name
+                    ),
+                    component.data.as_ref().and_then(|data| data.span),
+                    "data",
+                )),
+            }
+        }
+        errors
+    }
+
+    /// Renders the Rust file generated for the schema.
+    pub fn render(&self, modules: &ModuleMap) -> String {
+        pretty_print(self.generate(modules))
+    }
+
     pub fn generate_schema<P: AsRef<Path> + Clone>(&self, path: P) -> Result<(), std::io::Error> {
         std::fs::create_dir_all(path.clone()).map(|_| {
             let mut file = File::create(path.clone().as_ref().join(self.name.clone() + ".rs"))?;
-            writeln!(file, "{}\n", self.generate())?;
-            Ok(())
+            file.write_all(self.render(&ModuleMap::default()).as_bytes())
         })?
     }
 }
 
+/// Formats `tokens` as the contents of a Rust file.
+///
+/// The generated code is built from tokens, so it is well-formed by
+/// construction, and failing to parse it is a bug of the generator.
+pub fn pretty_print(tokens: TokenStream) -> String {
+    let file = syn::parse2::<syn::File>(tokens)
+        .unwrap_or_else(|e| panic!("the generated code is malformed: {}", e));
+    prettyplease::unparse(&file)
+}
+
 impl TryFrom<PathBuf> for SchemaFile {
-    type Error = String;
+    type Error = Vec<Diagnostic>;
 
     fn try_from(path: PathBuf) -> Result<Self, Self::Error> {
-        let filename = path
-            .file_stem()
-            .ok_or("Unable to get file stem")
-            .map(|s| s.to_str())?
-            .ok_or("Can't convert file stem to UTF-8")
-            .map(|s| s.to_string())?;
-        let mut file = File::open(path).map_err(|e| format!("Unable to open file: {}", e))?;
+        let mut file = File::open(&path).map_err(|e| {
+            vec![Diagnostic::new(
+                &path,
+                format!("Unable to open file: {}", e),
+            )]
+        })?;
         let mut contents = String::new();
-        file.read_to_string(&mut contents)
-            .map_err(|e| format!("Unable to read file: {}", e))?;
-        parse_schema(contents.as_bytes())
-            .map(|r| r.1)
-            .map_err(|e| format!("Unable to parse data: {}", e))
-            .map(|sb| sb.with_name(filename).build())?
-            .map_err(|e| format!("Cannot convert SchemaFile: {}", e))
+        file.read_to_string(&mut contents).map_err(|e| {
+            vec![Diagnostic::new(
+                &path,
+                format!("Unable to read file: {}", e),
+            )]
+        })?;
+        match Self::parse(path, &contents) {
+            (Some(schema), diagnostics) if diagnostics.is_empty() => Ok(schema),
+            (_, diagnostics) => Err(diagnostics),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn parse(source: &str) -> SchemaFile {
+        let (schema, errors) = SchemaFile::parse(PathBuf::from("a.schema"), source);
+        assert_eq!(errors, Vec::new());
+        schema.unwrap()
+    }
+
+    fn messages(errors: Vec<Diagnostic>) -> Vec<String> {
+        errors.into_iter().map(|error| error.message).collect()
+    }
+
+    fn locations(errors: Vec<Diagnostic>) -> Vec<(String, usize, usize)> {
+        errors
+            .into_iter()
+            .map(|error| {
+                let span = error.span.expect("the error has no location");
+                (error.message, span.start.line, span.start.column)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_file_name() {
+        for name in &["my-file.schema", "2d.schema"] {
+            let (schema, errors) = SchemaFile::parse(PathBuf::from(name), "package test;\n");
+            assert!(schema.is_some());
+            assert_eq!(errors.len(), 1);
+            assert!(errors[0].message.ends_with("which is not an identifier: file names take letters, digits and underscores, and do not start with a digit"));
+        }
+        let (_, errors) = SchemaFile::parse(PathBuf::from("health_2.schema"), "package test;\n");
+        assert_eq!(errors, Vec::new());
+        let (_, errors) = SchemaFile::parse(PathBuf::from("player/mod.schema"), "package test;\n");
+        assert_eq!(
+            messages(errors),
+            vec!["the file is generated into `mod.rs`, which declares the modules of its package"]
+        );
+    }
+
+    #[test]
+    fn test_parse_identifiers() {
+        let schema = parse(
+            "package Test.io;\n\
+             enum State { Alive = 0; DEAD = 1; }\n\
+             type Damage_Kind {\n\
+                 uint32 maxHp = 1;\n\
+                 uint32 _hidden = 2;\n\
+                 uint32 hp__x = 3;\n\
+                 uint32 hp_ = 4;\n\
+             }\n\
+             component Health {\n\
+                 id = 1000;\n\
+                 Damage_Kind data_kind = 1;\n\
+                 command Damage_Kind Do(Damage_Kind);\n\
+                 event Damage_Kind event_2;\n\
+             }\n",
+        );
+        assert_eq!(schema.package_name, vec!["Test", "io"]);
+        assert_eq!(schema.enums[0].values[0].name, "Alive");
+        assert_eq!(
+            schema.types[0]
+                .members
+                .iter()
+                .map(|member| member.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["maxHp", "_hidden", "hp__x", "hp_"]
+        );
+        let component = &schema.components[0];
+        assert_eq!(component.members[0].name, "data_kind");
+        assert_eq!(component.commands[0].name, "Do");
+        assert_eq!(component.events[0].name, "event_2");
+        assert_eq!(messages(schema.check_ids()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_pretty_print() {
+        assert_eq!(
+            pretty_print(quote! {
+                #[doc = " The mass of an entity."]
+                pub struct Mass { pub kg: f64, }
+            }),
+            "/// The mass of an entity.\npub struct Mass {\n    pub kg: f64,\n}\n"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "the generated code is malformed")]
+    fn test_pretty_print_malformed() {
+        pretty_print(quote!(pub struct));
+    }
+
+    #[test]
+    fn test_check_ids() {
+        let schema = parse(
+            "package test;\n\
+             enum Kind { A = 0; B = 1; A = 2; C = 1; }\n\
+             component Health {\n\
+                 id = 42;\n\
+                 uint32 hp = 0;\n\
+                 uint32 max_hp = 1;\n\
+                 uint32 regen = 1;\n\
+             }\n",
+        );
+        assert_eq!(
+            messages(schema.check_ids()),
+            vec![
+                "value `A` of enum `Kind` is defined twice",
+                "value `C` of enum `Kind` is 1, like `B`",
+                "component `Health` has id 42, while ids 0 to 99 are reserved for the standard library",
+                "field `hp` of `Health` has id 0, while field ids start at 1",
+                "field `regen` of `Health` has id 1, already used by `max_hp`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_command_names() {
+        let schema = parse(
+            "package test;\n\
+             type Hit {}\n\
+             component Health {\n\
+                 id = 1000;\n\
+                 command Hit heal(Hit);\n\
+                 command Hit heal(Hit);\n\
+                 command Hit heal_2(Hit);\n\
+                 command Hit heal2(Hit);\n\
+                 command Hit handle_request(Hit);\n\
+                 command Hit Heal(Hit);\n\
+                 command Hit doIt(Hit);\n\
+                 command Hit DOIT(Hit);\n\
+                 event Hit hit;\n\
+                 event Hit Hit;\n\
+             }\n",
+        );
+        assert_eq!(
+            messages(schema.check_ids()),
+            vec![
+                "event `Hit` of `Health` is generated as `HIT_EVENT_INDEX`, like `hit`",
+                "command `heal` of `Health` is defined twice",
+                "command `heal2` of `Health` is generated as `Heal2`, like `heal_2`",
+                "command `handle_request` of `Health` is named like the method handling all of them",
+                "command `Heal` of `Health` is generated as `Heal`, like `heal`",
+                "command `DOIT` of `Health` is generated as `DOIT_COMMAND_INDEX`, like `doIt`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_id_range() {
+        let schema = parse(
+            "package test;\n\
+             enum Kind { A = 4294967296; }\n\
+             component Health {\n\
+                 id = 5000000000;\n\
+                 uint32 hp = 4294967297;\n\
+             }\n",
+        );
+        assert_eq!(
+            locations(schema.check_ids()),
+            vec![
+                (
+                    "value `A` of enum `Kind` is 4294967296, while values go up to 4294967295"
+                        .to_string(),
+                    2,
+                    6
+                ),
+                (
+                    "component `Health` has id 5000000000, while ids go up to 4294967295"
+                        .to_string(),
+                    3,
+                    11
+                ),
+                (
+                    "field `hp` of `Health` has id 4294967297, while ids go up to 4294967295"
+                        .to_string(),
+                    5,
+                    13
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_payloads() {
+        let mut schema = parse(
+            "package test;\n\
+             enum Kind { A = 0; }\n\
+             type Hit { uint32 points = 1; }\n\
+             component Health {\n\
+                 id = 1000;\n\
+                 event Hit hit;\n\
+                 event Kind kind;\n\
+                 event uint32 points;\n\
+                 command Hit heal(Kind);\n\
+             }\n",
+        );
+        let source = schema.clone();
+        let imports = ImportGraph::new(vec![&source]);
+        assert_eq!(schema.resolve_references(&imports), Vec::new());
+        assert_eq!(
+            locations(schema.check_types(&imports)),
+            vec![
+                (
+                    "event `kind` of `Health` carries `test.Kind`, which is not a type".to_string(),
+                    7,
+                    12
+                ),
+                (
+                    "event `points` of `Health` carries `uint32`, which is not a type".to_string(),
+                    8,
+                    14
+                ),
+                (
+                    "command `heal` of `Health` takes `test.Kind`, which is not a type".to_string(),
+                    9,
+                    13
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_event_names() {
+        let schema = parse(
+            "package test;\n\
+             type Hit {}\n\
+             component Health {\n\
+                 id = 1000;\n\
+                 uint32 hp = 1;\n\
+                 event Hit hp;\n\
+                 event Hit hit;\n\
+                 event Hit hit;\n\
+             }\n",
+        );
+        assert_eq!(
+            messages(schema.check_ids()),
+            vec![
+                "event `hp` of `Health` is named like one of its fields",
+                "event `hit` of `Health` is defined twice",
+            ]
+        );
+        let mut schema = parse(
+            "package test;\n\
+             type Orbit { double radius = 1; }\n\
+             component Body {\n\
+                 id = 1000;\n\
+                 data Orbit;\n\
+                 event Orbit radius;\n\
+             }\n",
+        );
+        assert_eq!(messages(schema.check_ids()), Vec::<String>::new());
+        let source = schema.clone();
+        let imports = ImportGraph::new(vec![&source]);
+        assert_eq!(
+            locations(schema.expand_data(&imports)),
+            vec![(
+                "event `radius` of `Body` is named like one of its fields".to_string(),
+                6,
+                13
+            )]
+        );
+    }
+
+    #[test]
+    fn test_expand_data() {
+        let mut schema = parse(
+            "package test;\n\
+             enum Kind { A = 0; }\n\
+             type Orbit { double radius = 1; }\n\
+             component Body {\n\
+                 id = 1000;\n\
+                 data Orbit;\n\
+                 double radius = 2;\n\
+             }\n\
+             component Tag {\n\
+                 id = 1001;\n\
+                 data Kind;\n\
+             }\n",
+        );
+        let source = schema.clone();
+        let imports = ImportGraph::new(vec![&source]);
+        assert_eq!(
+            locations(schema.expand_data(&imports)),
+            vec![
+                (
+                    "field `radius` of component `Body` is already defined by its data type `Orbit`"
+                        .to_string(),
+                    7,
+                    8
+                ),
+                (
+                    "component `Tag` takes its data from `Kind`, which is not a type"
+                        .to_string(),
+                    11,
+                    1
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_command_args() {
+        let schema = parse(
+            "package test;\n\
+             type Hit {}\n\
+             component Health {\n\
+                 id = 1000;\n\
+                 command Hit heal();\n\
+                 command Hit hit(Hit);\n\
+                 command Hit hits(Hit, Hit);\n\
+             }\n",
+        );
+        assert_eq!(
+            messages(schema.check_ids()),
+            vec![
+                "command `heal` of `Health` takes 0 request types, while a command takes exactly one",
+                "command `hits` of `Health` takes 2 request types, while a command takes exactly one",
+            ]
+        );
     }
 }
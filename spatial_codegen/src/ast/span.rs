@@ -0,0 +1,62 @@
+use std::ops::Range;
+
+/// Where a node is in its schema.
+///
+/// Both ends count the bytes left after them, like `SyntaxError::remaining`,
+/// as the parsers only ever see what is left of the source.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct SourceSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl SourceSpan {
+    /// Returns the byte range of the node in `source`.
+    pub fn range(&self, source: &str) -> Range<usize> {
+        source.len().saturating_sub(self.start)..source.len().saturating_sub(self.end)
+    }
+
+    /// Narrows the span down to the first occurrence of `text` in it, if any.
+    pub fn find(&self, source: &str, text: &str) -> Range<usize> {
+        let range = self.range(source);
+        source
+            .get(range.clone())
+            .and_then(|node| node.find(text))
+            .map(|index| range.start + index..range.start + index + text.len())
+            .unwrap_or(range)
+    }
+}
+
+/// A problem found with a node, to be reported at the first occurrence of
+/// `text` within it.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Located {
+    pub message: String,
+    pub text: String,
+    pub span: Option<SourceSpan>,
+}
+
+impl Located {
+    pub fn new(message: String, span: Option<SourceSpan>, text: &str) -> Self {
+        Self {
+            message,
+            text: text.to_string(),
+            span,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let source = "type A { list<Damage> hits = 1; }";
+        let span = SourceSpan { start: 24, end: 2 };
+        assert_eq!(&source[span.range(source)], "list<Damage> hits = 1;");
+        assert_eq!(&source[span.find(source, "Damage")], "Damage");
+        assert_eq!(&source[span.find(source, "Hit")], "list<Damage> hits = 1;");
+    }
+}
@@ -1,44 +1,74 @@
 use crate::ast::ASTNode;
 use crate::ast::Component;
 use crate::ast::DataType;
+use crate::ast::ExportStrategy;
 use crate::ast::Member;
+use crate::ast::ModuleMap;
 use crate::ast::PackageNode;
 use crate::ast::SchemaFile;
 use crate::ast::Type;
 use crate::ast::AST;
+use std::path::PathBuf;
 
 fn generate_position_component() -> Component {
     Component {
         name: "Position".to_string(),
+        span: None,
+        doc: None,
+        annotations: Vec::new(),
+        types: Vec::new(),
+        enums: Vec::new(),
         id: 54,
         members: vec![Member {
             name: "coords".to_string(),
+            doc: None,
+            annotations: Vec::new(),
             m_type: DataType::UserDefined("Coordinates".to_string()),
             id: 1,
+            span: None,
+            boxed: false,
         }],
         commands: Vec::new(),
         events: Vec::new(),
+        data: None,
     }
 }
 
 fn generate_coordinates_type() -> Type {
     Type {
         name: "Coordinates".to_string(),
+        span: None,
+        doc: None,
+        annotations: Vec::new(),
+        types: Vec::new(),
+        enums: Vec::new(),
         members: vec![
             Member {
                 name: "x".to_string(),
+                doc: None,
+                annotations: Vec::new(),
                 m_type: DataType::Double,
                 id: 1,
+                span: None,
+                boxed: false,
             },
             Member {
                 name: "y".to_string(),
+                doc: None,
+                annotations: Vec::new(),
                 m_type: DataType::Double,
                 id: 2,
+                span: None,
+                boxed: false,
             },
             Member {
                 name: "z".to_string(),
+                doc: None,
+                annotations: Vec::new(),
                 m_type: DataType::Double,
                 id: 3,
+                span: None,
+                boxed: false,
             },
         ],
     }
@@ -47,10 +77,19 @@ fn generate_coordinates_type() -> Type {
 fn generate_worker_attribute_set_type() -> Type {
     Type {
         name: "WorkerAttributeSet".to_string(),
+        span: None,
+        doc: None,
+        annotations: Vec::new(),
+        types: Vec::new(),
+        enums: Vec::new(),
         members: vec![Member {
             name: "attribute".to_string(),
+            doc: None,
+            annotations: Vec::new(),
             m_type: DataType::List(Box::new(DataType::String)),
             id: 1,
+            span: None,
+            boxed: false,
         }],
     }
 }
@@ -58,12 +97,21 @@ fn generate_worker_attribute_set_type() -> Type {
 fn generate_worker_requirement_set_type() -> Type {
     Type {
         name: "WorkerRequirementSet".to_string(),
+        span: None,
+        doc: None,
+        annotations: Vec::new(),
+        types: Vec::new(),
+        enums: Vec::new(),
         members: vec![Member {
             name: "attribute".to_string(),
+            doc: None,
+            annotations: Vec::new(),
             m_type: DataType::List(Box::new(DataType::UserDefined(
                 "WorkerAttributeSet".to_string(),
             ))),
             id: 1,
+            span: None,
+            boxed: false,
         }],
     }
 }
@@ -71,48 +119,78 @@ fn generate_worker_requirement_set_type() -> Type {
 fn generate_entity_acl_component() -> Component {
     Component {
         name: "EntityAcl".to_string(),
+        span: None,
+        doc: None,
+        annotations: Vec::new(),
+        types: Vec::new(),
+        enums: Vec::new(),
         id: 50,
         members: vec![
             Member {
                 name: "read_acl".to_string(),
+                doc: None,
+                annotations: Vec::new(),
                 m_type: DataType::UserDefined("WorkerRequirementSet".to_string()),
                 id: 1,
+                span: None,
+                boxed: false,
             },
             Member {
                 name: "component_write_acl".to_string(),
+                doc: None,
+                annotations: Vec::new(),
                 m_type: DataType::Map(
                     Box::new(DataType::Uint32),
                     Box::new(DataType::UserDefined("WorkerRequirementSet".to_string())),
                 ),
-                id: 1,
+                id: 2,
+                span: None,
+                boxed: false,
             },
         ],
         events: Vec::new(),
         commands: Vec::new(),
+        data: None,
     }
 }
 
 fn generate_persistence_component() -> Component {
     Component {
         name: "Persistence".to_string(),
+        span: None,
+        doc: None,
+        annotations: Vec::new(),
+        types: Vec::new(),
+        enums: Vec::new(),
         id: 55,
         members: Vec::new(),
         events: Vec::new(),
         commands: Vec::new(),
+        data: None,
     }
 }
 
 fn generate_metadata_component() -> Component {
     Component {
         name: "Metadata".to_string(),
+        span: None,
+        doc: None,
+        annotations: Vec::new(),
+        types: Vec::new(),
+        enums: Vec::new(),
         id: 53,
         members: vec![Member {
             name: "entity_type".to_string(),
+            doc: None,
+            annotations: Vec::new(),
             m_type: DataType::String,
             id: 1,
+            span: None,
+            boxed: false,
         }],
         events: Vec::new(),
         commands: Vec::new(),
+        data: None,
     }
 }
 
@@ -121,10 +199,16 @@ fn generate_metadata_component() -> Component {
 fn generate_system_component() -> Component {
     Component {
         name: "System".to_string(),
+        span: None,
+        doc: None,
+        annotations: Vec::new(),
+        types: Vec::new(),
+        enums: Vec::new(),
         id: 59,
         members: Vec::new(),
         events: Vec::new(),
         commands: Vec::new(),
+        data: None,
     }
 }
 
@@ -133,21 +217,38 @@ fn generate_system_component() -> Component {
 fn generate_player_identity_type() -> Type {
     Type {
         name: "PlayerIdentity".to_string(),
+        span: None,
+        doc: None,
+        annotations: Vec::new(),
+        types: Vec::new(),
+        enums: Vec::new(),
         members: vec![
             Member {
                 name: "player_identifier".to_string(),
+                doc: None,
+                annotations: Vec::new(),
                 m_type: DataType::String,
                 id: 1,
+                span: None,
+                boxed: false,
             },
             Member {
                 name: "provider".to_string(),
+                doc: None,
+                annotations: Vec::new(),
                 m_type: DataType::String,
                 id: 2,
+                span: None,
+                boxed: false,
             },
             Member {
                 name: "metadata".to_string(),
+                doc: None,
+                annotations: Vec::new(),
                 m_type: DataType::Bytes,
                 id: 3,
+                span: None,
+                boxed: false,
             },
         ],
     }
@@ -156,14 +257,24 @@ fn generate_player_identity_type() -> Type {
 fn generate_player_client_component() -> Component {
     Component {
         name: "PlayerClient".to_string(),
+        span: None,
+        doc: None,
+        annotations: Vec::new(),
+        types: Vec::new(),
+        enums: Vec::new(),
         id: 61,
         members: vec![Member {
             name: "player_identity".to_string(),
+            doc: None,
+            annotations: Vec::new(),
             m_type: DataType::UserDefined("PlayerIdentity".to_string()),
             id: 1,
+            span: None,
+            boxed: false,
         }],
         events: Vec::new(),
         commands: Vec::new(),
+        data: None,
     }
 }
 
@@ -171,6 +282,9 @@ fn generate_improbable_restricted_schema_file() -> SchemaFile {
     SchemaFile {
         package_name: vec!["improbable".to_string(), "restricted".to_string()],
         name: "standard_library".to_string(),
+        path: PathBuf::from("improbable/restricted/standard_library.schema"),
+        import_path: PathBuf::from("improbable/restricted/standard_library.schema"),
+        imports: Vec::new(),
         components: vec![
             generate_system_component(),
             // Add Worker Component
@@ -183,6 +297,7 @@ fn generate_improbable_restricted_schema_file() -> SchemaFile {
             generate_player_identity_type(),
         ],
         enums: Vec::new(),
+        source: String::new(),
     }
 }
 
@@ -190,6 +305,9 @@ fn generate_improbabled_schema_file() -> SchemaFile {
     SchemaFile {
         package_name: vec!["improbable".to_string()],
         name: "standard_library".to_string(),
+        path: PathBuf::from("improbable/standard_library.schema"),
+        import_path: PathBuf::from("improbable/standard_library.schema"),
+        imports: Vec::new(),
         components: vec![
             generate_position_component(),
             generate_entity_acl_component(),
@@ -204,6 +322,7 @@ fn generate_improbabled_schema_file() -> SchemaFile {
             // Add ComponentInterest Type
         ],
         enums: Vec::new(),
+        source: String::new(),
     }
 }
 
@@ -229,5 +348,7 @@ fn generate_improbable_package() -> PackageNode {
 pub fn generate_standard_library() -> AST {
     AST {
         inner: vec![ASTNode::PackageNode(generate_improbable_package())],
+        exports: ExportStrategy::default(),
+        modules: ModuleMap::default(),
     }
 }
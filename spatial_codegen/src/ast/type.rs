@@ -1,26 +1,111 @@
+use crate::ast::generate_doc;
+use crate::ast::ident;
+use crate::ast::type_graph::Field;
+use crate::ast::Annotation;
+use crate::ast::Enum;
+use crate::ast::Located;
 use crate::ast::Member;
-use std::convert::identity;
+use crate::ast::Nested;
+use crate::ast::Resolve;
+use crate::ast::SourceSpan;
+use crate::ast::Unresolved;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::HashSet;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Type {
     pub name: String,
+    pub doc: Option<String>,
+    pub annotations: Vec<Annotation>,
     pub members: Vec<Member>,
+    pub types: Vec<Type>,
+    pub enums: Vec<Enum>,
+    pub span: Option<SourceSpan>,
 }
 
 impl Type {
-    pub fn generate_one(&self) -> String {
-        format!(
-            "{}\nstruct {} {{{}}}",
-            "#[spatial_type]",
-            self.name,
-            Member::generate_multiple(&self.members)
-        )
+    pub fn nested(&self) -> Nested<'_> {
+        Nested {
+            parent: &self.name,
+            types: &self.types,
+            enums: &self.enums,
+        }
     }
 
-    pub fn generate_multiple(data: &[Self]) -> String {
+    pub fn generate_one(&self) -> TokenStream {
+        let nested = self.nested();
+        let doc = generate_doc(&self.doc);
+        let annotations = Annotation::generate_multiple(&self.annotations);
+        let name = ident(&self.name);
+        let members = Member::generate_multiple(&nested.qualify(&self.members));
+        let nested = nested.generate();
+        quote! {
+            #doc
+            #annotations
+            #[spatial_type]
+            struct #name {
+                #members
+            }
+
+            #nested
+        }
+    }
+
+    pub fn generate_multiple(data: &[Self]) -> TokenStream {
+        data.iter().map(Type::generate_one).collect()
+    }
+
+    pub fn get_references(data: &[Self]) -> Vec<&str> {
+        data.iter()
+            .flat_map(|t| {
+                let mut references = Member::get_references(&t.members);
+                references.extend(Type::get_references(&t.types));
+                t.nested().filter_references(references)
+            })
+            .collect()
+    }
+
+    pub fn resolve_references(data: &mut [Self], resolve: &Resolve, errors: &mut Vec<Unresolved>) {
+        for t in data {
+            let resolve = t.nested().scope(resolve);
+            Member::resolve_references(&mut t.members, &resolve, errors);
+            Type::resolve_references(&mut t.types, &resolve, errors);
+        }
+    }
+
+    pub fn check_types(data: &[Self], is_enum: &dyn Fn(&str) -> bool) -> Vec<Located> {
         data.iter()
-            .map(Type::generate_one)
-            .fold(String::new(), |acc, val| acc + "\n\n" + &val)
+            .flat_map(|t| {
+                let is_enum = t.nested().enum_scope(is_enum);
+                let mut errors = Member::check_types(&t.members, &t.name, &is_enum);
+                errors.extend(Type::check_types(&t.types, &is_enum));
+                errors
+            })
+            .collect()
+    }
+
+    /// Boxes the fields listed in `boxed`, the types being keyed under
+    /// `scope`, see `TypeGraph`.
+    pub fn box_fields(data: &mut [Self], scope: &str, boxed: &HashSet<Field>) {
+        for t in data {
+            let key = format!("{}.{}", scope, t.name);
+            for member in &mut t.members {
+                member.boxed = boxed.contains(&(key.clone(), member.name.clone()));
+            }
+            Type::box_fields(&mut t.types, &key, boxed);
+        }
+    }
+
+    pub fn check_ids(data: &[Self]) -> Vec<Located> {
+        data.iter()
+            .flat_map(|t| {
+                let mut errors = Member::check_ids(&t.members, &t.name);
+                errors.extend(Enum::check_values(&t.enums));
+                errors.extend(Type::check_ids(&t.types));
+                errors
+            })
+            .collect()
     }
 
     pub fn get_export(&self) -> Option<String> {
@@ -29,8 +114,7 @@ impl Type {
 
     pub fn get_exports(data: &[Self]) -> Vec<String> {
         data.iter()
-            .map(Self::get_export)
-            .filter_map(identity)
+            .flat_map(|t| t.get_export().into_iter().chain(t.nested().get_export()))
             .collect()
     }
 }
@@ -0,0 +1,274 @@
+use crate::ast::DataType;
+use crate::ast::ExportStrategy;
+use crate::ast::Member;
+use crate::ast::SchemaFile;
+use crate::ast::SourceSpan;
+use crate::ast::Type;
+use crate::diagnostic::Diagnostic;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+/// A field of a type, by the key of the type, then the field name.
+pub type Field = (String, String);
+
+/// A field storing a type inline.
+#[derive(Debug)]
+struct Edge {
+    field: String,
+    span: Option<SourceSpan>,
+    target: String,
+    optional: bool,
+}
+
+/// The user-defined types of a set of schemas, linked by the fields which
+/// store another type inline, and so make their type at least as large:
+/// plain and `option` fields, as lists and maps keep their items apart.
+///
+/// Types are keyed by their name qualified with the scope of their file,
+/// like resolved references, which nested types extend with their own
+/// name, as in `io.nebulis.PlayerHealth.Damage`.
+pub struct TypeGraph<'a> {
+    edges: HashMap<String, Vec<Edge>>,
+    schemas: HashMap<String, &'a SchemaFile>,
+}
+
+/// The types visible from where a reference is made, innermost last: the
+/// key of each type or component the reference is nested in, along with
+/// the names of the types nested in it.
+type Scopes<'a> = Vec<(String, Vec<&'a str>)>;
+
+impl<'a> TypeGraph<'a> {
+    pub fn new<I: IntoIterator<Item = &'a SchemaFile>>(
+        schemas: I,
+        exports: ExportStrategy,
+    ) -> Self {
+        let mut graph = Self {
+            edges: HashMap::new(),
+            schemas: HashMap::new(),
+        };
+        for schema in schemas {
+            let scope = schema.scope(exports);
+            for t in &schema.types {
+                let key = format!("{}.{}", scope, t.name);
+                graph.add_type(schema, key, t, &mut Vec::new());
+            }
+            for c in &schema.components {
+                let key = format!("{}.{}", scope, c.name);
+                let names = c.types.iter().map(|t| t.name.as_str()).collect();
+                let mut scopes = vec![(key.clone(), names)];
+                for t in &c.types {
+                    graph.add_type(schema, format!("{}.{}", key, t.name), t, &mut scopes);
+                }
+            }
+        }
+        graph
+    }
+
+    fn add_type(
+        &mut self,
+        schema: &'a SchemaFile,
+        key: String,
+        t: &'a Type,
+        scopes: &mut Scopes<'a>,
+    ) {
+        scopes.push((
+            key.clone(),
+            t.types.iter().map(|t| t.name.as_str()).collect(),
+        ));
+        let edges = t
+            .members
+            .iter()
+            .filter_map(|member| Self::get_edge(member, scopes))
+            .collect();
+        for nested in &t.types {
+            self.add_type(schema, format!("{}.{}", key, nested.name), nested, scopes);
+        }
+        scopes.pop();
+        self.edges.insert(key.clone(), edges);
+        self.schemas.insert(key, schema);
+    }
+
+    /// Returns the type `member` stores inline, if any. Its references are
+    /// expected to be resolved, so a plain name is one of a nested type.
+    fn get_edge(member: &Member, scopes: &[(String, Vec<&str>)]) -> Option<Edge> {
+        let (name, optional) = match &member.m_type {
+            DataType::UserDefined(name) => (name, false),
+            DataType::Option(inner) => match &**inner {
+                DataType::UserDefined(name) => (name, true),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let target = if name.contains('.') {
+            name.clone()
+        } else {
+            scopes
+                .iter()
+                .rev()
+                .find(|(_, names)| names.contains(&name.as_str()))
+                .map(|(key, _)| format!("{}.{}", key, name))?
+        };
+        Some(Edge {
+            field: member.name.clone(),
+            span: member.span,
+            target,
+            optional,
+        })
+    }
+
+    fn visit<'b>(
+        &'b self,
+        key: &'b str,
+        removed: &HashSet<Field>,
+        stack: &mut Vec<(&'b str, &'b Edge)>,
+        done: &mut HashSet<&'b str>,
+    ) -> Option<Vec<(&'b str, &'b Edge)>> {
+        if done.contains(key) {
+            return None;
+        }
+        for edge in self.edges.get(key).into_iter().flatten() {
+            if removed.contains(&(key.to_string(), edge.field.clone())) {
+                continue;
+            }
+            stack.push((key, edge));
+            if let Some(start) = stack.iter().position(|(from, _)| *from == edge.target) {
+                return Some(stack[start..].to_vec());
+            }
+            if let Some(cycle) = self.visit(&edge.target, removed, stack, done) {
+                return Some(cycle);
+            }
+            stack.pop();
+        }
+        done.insert(key);
+        None
+    }
+
+    /// Returns the fields of a type containing itself, leaving out the
+    /// fields in `removed`.
+    fn find_cycle(&self, removed: &HashSet<Field>) -> Option<Vec<(&str, &Edge)>> {
+        let mut keys = self.edges.keys().collect::<Vec<&String>>();
+        keys.sort();
+        let mut done = HashSet::new();
+        keys.into_iter()
+            .find_map(|key| self.visit(key, removed, &mut Vec::new(), &mut done))
+    }
+
+    /// Returns the fields to box so that no type contains itself, and the
+    /// types which do through plain fields only, as they could never be
+    /// built.
+    ///
+    /// Each cycle is broken at its last `option` field, so a type
+    /// referring to itself gets the field doing so boxed.
+    pub fn find_boxed(&self) -> (HashSet<Field>, Vec<Diagnostic>) {
+        let mut removed = HashSet::new();
+        let mut boxed = HashSet::new();
+        let mut errors = Vec::new();
+        while let Some(cycle) = self.find_cycle(&removed) {
+            let (key, edge) = cycle
+                .iter()
+                .rev()
+                .find(|(_, edge)| edge.optional)
+                .unwrap_or(&cycle[cycle.len() - 1]);
+            let field = (key.to_string(), edge.field.clone());
+            if edge.optional {
+                boxed.insert(field.clone());
+            } else {
+                let fields = cycle
+                    .iter()
+                    .map(|(key, edge)| format!("`{}.{}`", key, edge.field))
+                    .collect::<Vec<String>>()
+                    .join(" -> ");
+                let (start, first) = cycle[0];
+                errors.push(self.schemas[start].locate(
+                    format!(
+                        "type `{}` would be infinitely large, as it contains itself through {}",
+                        start, fields
+                    ),
+                    first.span,
+                    &first.field,
+                ));
+            }
+            removed.insert(field);
+        }
+        (boxed, errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::path::PathBuf;
+
+    fn parse(path: &str, source: &str) -> SchemaFile {
+        let (schema, errors) = SchemaFile::parse(PathBuf::from(path), source);
+        assert_eq!(errors, Vec::new());
+        schema.unwrap()
+    }
+
+    fn field(key: &str, name: &str) -> Field {
+        (key.to_string(), name.to_string())
+    }
+
+    #[test]
+    fn test_find_boxed() {
+        let schema = parse(
+            "a.schema",
+            "package test;\n\
+             type Node { uint32 value = 1; option<test.Node> next = 2; list<test.Node> children = 3; }\n\
+             type A { option<test.B> b = 1; }\n\
+             type B { test.A a = 1; }\n\
+             component Tree {\n\
+                 id = 1000;\n\
+                 type Branch { option<Branch> left = 1; option<Branch> right = 2; }\n\
+                 Branch root = 1;\n\
+             }\n",
+        );
+        let (boxed, errors) = TypeGraph::new(vec![&schema], ExportStrategy::Flatten).find_boxed();
+        assert_eq!(errors, Vec::new());
+        assert_eq!(
+            boxed,
+            vec![
+                field("test.Node", "next"),
+                field("test.A", "b"),
+                field("test.Tree.Branch", "left"),
+                field("test.Tree.Branch", "right"),
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn test_find_infinite() {
+        let schema = parse(
+            "a.schema",
+            "package test;\n\
+             type A { test.B b = 1; }\n\
+             type B { test.A a = 1; option<test.B> next = 2; }\n",
+        );
+        let (boxed, errors) = TypeGraph::new(vec![&schema], ExportStrategy::Flatten).find_boxed();
+        assert_eq!(boxed, vec![field("test.B", "next")].into_iter().collect());
+        let located = errors
+            .into_iter()
+            .map(|error| {
+                let span = error.span.expect("the error has no location");
+                (
+                    error.path,
+                    error.message,
+                    span.start.line,
+                    span.start.column,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            located,
+            vec![(
+                PathBuf::from("a.schema"),
+                "type `test.A` would be infinitely large, as it contains itself through `test.A.b` -> `test.B.a`".to_string(),
+                2,
+                17
+            )]
+        );
+    }
+}
@@ -1,29 +1,41 @@
+use crate::ast::generate_doc;
+use crate::ast::ident;
+use crate::ast::pascal_case;
+use proc_macro2::Ident;
+use proc_macro2::Literal;
+use proc_macro2::TokenStream;
+use quote::quote;
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Value {
     pub name: String,
+    pub doc: Option<String>,
     pub id: usize,
 }
 
 impl Value {
-    pub fn generate_one(&self) -> String {
-        format!("    {}", self.name)
+    /// The name of the variant, in Rust style: `RESPAWNING` becomes
+    /// `Respawning`.
+    pub fn variant(&self) -> Ident {
+        ident(&pascal_case(&self.name))
     }
 
-    pub fn generate_multiple(data: &[Self]) -> String {
-        if !data.is_empty() {
-            let values = data
-                .iter()
-                .map(Self::generate_one)
-                .fold(String::new(), |acc, val| {
-                    if !acc.is_empty() {
-                        acc + ",\n" + &val
-                    } else {
-                        val
-                    }
-                });
-            "\n".to_string() + &values + "\n"
-        } else {
-            "".to_string()
+    /// Generates the value as a variant with its schema value as the
+    /// discriminant, keeping its schema name for the `spatial_enum` macro.
+    pub fn generate_one(&self) -> TokenStream {
+        let doc = generate_doc(&self.doc);
+        let name = &self.name;
+        let variant = self.variant();
+        let id = Literal::usize_unsuffixed(self.id);
+        quote! {
+            #doc
+            #[spatial(name = #name)]
+            #variant = #id
         }
     }
+
+    pub fn generate_multiple(data: &[Self]) -> TokenStream {
+        let values = data.iter().map(Self::generate_one);
+        quote! { #(#values),* }
+    }
 }
@@ -0,0 +1,237 @@
+use crate::ast::ExportStrategy;
+use crate::ast::ModuleMap;
+use crate::ast::AST;
+use crate::diagnostic::CodegenError;
+use crate::diagnostic::Diagnostic;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// The name of the file `Builder` declares the generated modules in.
+pub const ENTRY_FILE: &str = "spatial_schema.rs";
+
+/// Generates the modules of a set of schemas from a build script.
+///
+/// ```ignore
+/// // build.rs
+/// fn main() {
+///     spatial_codegen::Builder::new()
+///         .with_schema_root("schema")
+///         .generate()
+///         .unwrap_or_else(|error| panic!("\n{}", error));
+/// }
+///
+/// // src/main.rs
+/// mod schema {
+///     include!(concat!(env!("OUT_DIR"), "/spatial_schema.rs"));
+/// }
+/// ```
+///
+/// Unlike `AST::generate`, only the files whose contents changed are
+/// written, so rebuilding does not recompile the generated code. Files no
+/// longer generated are left in place, out of the module tree.
+#[derive(Debug, Default)]
+pub struct Builder {
+    roots: Vec<PathBuf>,
+    out_dir: Option<PathBuf>,
+    exports: ExportStrategy,
+    modules: ModuleMap,
+    single_file: bool,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a directory to load schema files from.
+    pub fn with_schema_root<P: AsRef<Path>>(mut self, root: P) -> Self {
+        self.roots.push(root.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the directory to generate into, `OUT_DIR` by default.
+    pub fn with_out_dir<P: AsRef<Path>>(mut self, out_dir: P) -> Self {
+        self.out_dir = Some(out_dir.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn with_exports(mut self, exports: ExportStrategy) -> Self {
+        self.exports = exports;
+        self
+    }
+
+    /// Sets how packages map to the generated modules.
+    pub fn with_modules(mut self, modules: ModuleMap) -> Self {
+        self.modules = modules;
+        self
+    }
+
+    /// Generates every module into the file to `include!`, rather than
+    /// into a tree of files declared by it.
+    pub fn with_single_file(mut self, single_file: bool) -> Self {
+        self.single_file = single_file;
+        self
+    }
+
+    /// Loads the schemas and generates their modules, along with the file
+    /// to `include!` them from, whose path is returned.
+    ///
+    /// Cargo is told to run the build script again when a schema changes,
+    /// and every diagnostic is printed as a `cargo:warning` before the
+    /// error is returned.
+    pub fn generate(self) -> Result<PathBuf, CodegenError> {
+        for root in &self.roots {
+            println!("cargo:rerun-if-changed={}", root.display());
+        }
+        let result = self.load().and_then(|ast| {
+            // The standard library is built in, so has no file to watch.
+            for schema in ast.get_schemas() {
+                if schema.path.is_file() {
+                    println!("cargo:rerun-if-changed={}", schema.path.display());
+                }
+            }
+            self.write(&ast)
+        });
+        if let Err(error) = &result {
+            for diagnostic in &error.diagnostics {
+                for line in diagnostic.to_string().lines() {
+                    println!("cargo:warning={}", line);
+                }
+            }
+        }
+        result
+    }
+
+    fn load(&self) -> Result<AST, CodegenError> {
+        if self.roots.is_empty() {
+            return Err(
+                Diagnostic::new("build.rs", "no schema root was given to the builder").into(),
+            );
+        }
+        AST::load_roots(&self.roots, self.exports, self.modules.clone())
+    }
+
+    fn write(&self, ast: &AST) -> Result<PathBuf, CodegenError> {
+        let out_dir = match &self.out_dir {
+            Some(out_dir) => out_dir.clone(),
+            None => std::env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or_else(|| {
+                    Diagnostic::new(
+                        "build.rs",
+                        "OUT_DIR is not set, as the builder is not run from a build script",
+                    )
+                })?,
+        };
+        let error = |path: &Path, e: std::io::Error| {
+            Diagnostic::new(path, format!("Unable to write file: {}", e))
+        };
+        std::fs::create_dir_all(&out_dir).map_err(|e| error(&out_dir, e))?;
+        // The entry points to the modules by absolute path.
+        let out_dir = out_dir.canonicalize().map_err(|e| error(&out_dir, e))?;
+        let entry = out_dir.join(ENTRY_FILE);
+        if self.single_file {
+            write_if_changed(&entry, &ast.render_file()).map_err(|e| error(&entry, e))?;
+            return Ok(entry);
+        }
+        let files = ast
+            .render()
+            .into_iter()
+            .map(|(path, contents)| (out_dir.join(path), contents))
+            .filter(|(path, _)| path != &out_dir.join("mod.rs"))
+            .chain(std::iter::once((entry.clone(), ast.render_entry(&out_dir))));
+        for (path, contents) in files {
+            write_if_changed(&path, &contents).map_err(|e| error(&path, e))?;
+        }
+        Ok(entry)
+    }
+}
+
+/// Writes `contents` to `path` unless it already holds them, and returns
+/// whether it did.
+pub(crate) fn write_if_changed(path: &Path, contents: &str) -> std::io::Result<bool> {
+    if std::fs::read(path).ok().as_deref() == Some(contents.as_bytes()) {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_write_if_changed() {
+        let dir = std::env::temp_dir().join(format!("spatial_codegen_{}", std::process::id()));
+        let path = dir.join("io").join("mod.rs");
+        assert!(write_if_changed(&path, "pub mod nebulis;\n").unwrap());
+        assert!(!write_if_changed(&path, "pub mod nebulis;\n").unwrap());
+        assert!(write_if_changed(&path, "pub mod improbable;\n").unwrap());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "pub mod improbable;\n"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_generate() {
+        let dir =
+            std::env::temp_dir().join(format!("spatial_codegen_builder_{}", std::process::id()));
+        let schema = dir.join("schema");
+        std::fs::create_dir_all(&schema).unwrap();
+        std::fs::write(
+            schema.join("mass.schema"),
+            "package test;\ncomponent Mass { id = 1000; double mass = 1; }\n",
+        )
+        .unwrap();
+        let entry = Builder::new()
+            .with_schema_root(&schema)
+            .with_out_dir(dir.join("out"))
+            .generate()
+            .unwrap();
+        let out_dir = dir.join("out").canonicalize().unwrap();
+        assert_eq!(entry, out_dir.join(ENTRY_FILE));
+        let contents = std::fs::read_to_string(&entry).unwrap();
+        assert!(!contents.contains("#!["));
+        assert!(contents.contains(&format!(
+            "#[path = {:?}]\npub mod test;\n",
+            out_dir.join("test/mod.rs").display().to_string()
+        )));
+        assert!(out_dir.join("test").join("mass.rs").is_file());
+        assert!(!out_dir.join("mod.rs").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_generate_single_file() {
+        let dir =
+            std::env::temp_dir().join(format!("spatial_codegen_single_{}", std::process::id()));
+        let schema = dir.join("schema");
+        std::fs::create_dir_all(&schema).unwrap();
+        std::fs::write(
+            schema.join("mass.schema"),
+            "package test;\ncomponent Mass { id = 1000; double mass = 1; }\n",
+        )
+        .unwrap();
+        let entry = Builder::new()
+            .with_schema_root(&schema)
+            .with_out_dir(dir.join("out"))
+            .with_single_file(true)
+            .generate()
+            .unwrap();
+        let contents = std::fs::read_to_string(&entry).unwrap();
+        let files = std::fs::read_dir(dir.join("out")).unwrap().count();
+        std::fs::remove_dir_all(dir).unwrap();
+        assert_eq!(files, 1);
+        assert!(!contents.contains("#[path"));
+        assert!(contents.contains("\npub mod test {\n    #![allow(deprecated, non_camel_case_types, non_snake_case)]\n    mod mass {\n"));
+        assert!(contents.contains("    pub use mass::Mass;\n"));
+        assert!(contents.contains("\npub mod improbable {\n"));
+    }
+}
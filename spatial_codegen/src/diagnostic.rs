@@ -0,0 +1,190 @@
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    fn from_offset(source: &str, offset: usize) -> Self {
+        let offset = offset.min(source.len());
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: source[line_start..offset].chars().count() + 1,
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Span {
+    pub start: Location,
+    pub end: Location,
+    pub source_line: String,
+}
+
+impl Span {
+    pub fn new(source: &str, range: Range<usize>) -> Self {
+        let start = Location::from_offset(source, range.start);
+        let end = Location::from_offset(source, range.end.max(range.start));
+        let source_line = source
+            .lines()
+            .nth(start.line - 1)
+            .unwrap_or_default()
+            .to_string();
+        Self {
+            start,
+            end,
+            source_line,
+        }
+    }
+
+    fn caret(&self) -> String {
+        let padding = self
+            .source_line
+            .chars()
+            .take(self.start.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        let length = if self.end.line == self.start.line {
+            self.end.column.saturating_sub(self.start.column).max(1)
+        } else {
+            1
+        };
+        padding + &"^".repeat(length)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub message: String,
+    pub expected: Vec<String>,
+    pub span: Option<Span>,
+}
+
+impl Diagnostic {
+    pub fn new<P: AsRef<Path>, S: ToString>(path: P, message: S) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            message: message.to_string(),
+            expected: Vec::new(),
+            span: None,
+        }
+    }
+
+    pub fn with_span(mut self, source: &str, range: Range<usize>) -> Self {
+        self.span = Some(Span::new(source, range));
+        self
+    }
+
+    pub fn with_expected(mut self, expected: Vec<String>) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    fn expected_message(&self) -> Option<String> {
+        let (last, rest) = self.expected.split_last()?;
+        Some(if rest.is_empty() {
+            format!("expected {}", last)
+        } else {
+            format!("expected {} or {}", rest.join(", "), last)
+        })
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.expected_message() {
+            Some(expected) => writeln!(f, "error: {}, {}", expected, self.message)?,
+            None => writeln!(f, "error: {}", self.message)?,
+        }
+        match &self.span {
+            Some(span) => {
+                let gutter = " ".repeat(span.start.line.to_string().len());
+                writeln!(
+                    f,
+                    "{}--> {}:{}:{}",
+                    gutter,
+                    self.path.display(),
+                    span.start.line,
+                    span.start.column
+                )?;
+                writeln!(f, "{} |", gutter)?;
+                writeln!(f, "{} | {}", span.start.line, span.source_line)?;
+                write!(f, "{} | {}", gutter, span.caret())
+            }
+            None => write!(f, " --> {}", self.path.display()),
+        }
+    }
+}
+
+/// Why schemas could not be loaded, with every diagnostic found.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct CodegenError {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for diagnostic in &self.diagnostics {
+            writeln!(f, "{}\n", diagnostic)?;
+        }
+        write!(
+            f,
+            "error: could not load the schemas due to {} previous error{}",
+            self.diagnostics.len(),
+            if self.diagnostics.len() == 1 { "" } else { "s" }
+        )
+    }
+}
+
+impl std::error::Error for CodegenError {}
+
+impl From<Diagnostic> for CodegenError {
+    fn from(diagnostic: Diagnostic) -> Self {
+        Self {
+            diagnostics: vec![diagnostic],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_span() {
+        let source = "package io;\n\ntype A {\n\tuint32 a 1;\n}\n";
+        let offset = source.find('1').unwrap();
+        let span = Span::new(source, offset..offset + 1);
+        assert_eq!(
+            span.start,
+            Location {
+                line: 4,
+                column: 11
+            }
+        );
+        assert_eq!(span.source_line, "\tuint32 a 1;");
+        assert_eq!(span.caret(), "\t         ^");
+    }
+
+    #[test]
+    fn test_display() {
+        let source = "package io;\ntype A { uint32 a 1; }\n";
+        let offset = source.find('1').unwrap();
+        let diagnostic = Diagnostic::new("a.schema", "found `1`")
+            .with_span(source, offset..offset + 1)
+            .with_expected(vec!["`=`".to_string()]);
+        assert_eq!(
+            diagnostic.to_string(),
+            "error: expected `=`, found `1`\n --> a.schema:2:19\n  |\n2 | type A { uint32 a 1; }\n  |                   ^"
+        );
+    }
+}
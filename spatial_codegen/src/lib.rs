@@ -1,4 +1,15 @@
 mod ast;
+mod builder;
+mod diagnostic;
 mod parser;
 
+pub use ast::ExportStrategy;
+pub use ast::ModuleMap;
+pub use ast::SchemaFile;
 pub use ast::AST;
+pub use builder::Builder;
+pub use builder::ENTRY_FILE;
+pub use diagnostic::CodegenError;
+pub use diagnostic::Diagnostic;
+pub use diagnostic::Location;
+pub use diagnostic::Span;
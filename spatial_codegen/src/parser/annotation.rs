@@ -0,0 +1,204 @@
+use crate::ast::Annotation;
+use crate::ast::AnnotationValue;
+use crate::parser::comment::space0;
+use crate::parser::error::SyntaxError;
+use nom::alt;
+use nom::char;
+use nom::character::complete::alpha1;
+use nom::character::complete::digit1;
+use nom::character::is_alphanumeric;
+use nom::complete;
+use nom::delimited;
+use nom::do_parse;
+use nom::many0;
+use nom::map;
+use nom::map_res;
+use nom::named;
+use nom::not;
+use nom::opt;
+use nom::pair;
+use nom::recognize;
+use nom::return_error;
+use nom::separated_list;
+use nom::tag;
+use nom::take_while;
+use nom::terminated;
+use nom::tuple;
+
+use crate::parser::utils::identifier as parse_field_name;
+
+named!(
+    parse_annotation_name<&[u8], String, SyntaxError>,
+    map_res!(
+        recognize!(pair!(
+            alpha1,
+            complete!(take_while!(|c| is_alphanumeric(c) || c == b'_' || c == b'.'))
+        )),
+        |s| std::str::from_utf8(s).map(str::to_string)
+    )
+);
+
+named!(
+    parse_number<&[u8], String, SyntaxError>,
+    map_res!(
+        recognize!(tuple!(
+            opt!(char!('-')),
+            digit1,
+            opt!(complete!(pair!(char!('.'), digit1)))
+        )),
+        |s| std::str::from_utf8(s).map(str::to_string)
+    )
+);
+
+named!(
+    parse_string<&[u8], String, SyntaxError>,
+    map_res!(
+        delimited!(
+            char!('"'),
+            complete!(take_while!(|c| c != b'"' && c != b'\n')),
+            return_error!(expect!("`\"`", char!('"')))
+        ),
+        |s| std::str::from_utf8(s).map(str::to_string)
+    )
+);
+
+named!(
+    parse_list<&[u8], Vec<AnnotationValue>, SyntaxError>,
+    delimited!(
+        char!('['),
+        delimited!(
+            space0,
+            separated_list!(delimited!(space0, char!(','), space0), parse_annotation_value),
+            space0
+        ),
+        return_error!(expect!("an annotation value or `]`", char!(']')))
+    )
+);
+
+named!(
+    parse_annotation_field<&[u8], (String, AnnotationValue), SyntaxError>,
+    do_parse!(
+        name: expect!("a field name", parse_field_name)
+            >> space0
+            >> return_error!(expect!("`=`", char!('=')))
+            >> space0
+            >> value: return_error!(expect!("an annotation value", parse_annotation_value))
+            >> (name, value)
+    )
+);
+
+named!(
+    parse_annotation_fields<&[u8], Vec<(String, AnnotationValue)>, SyntaxError>,
+    delimited!(
+        char!('('),
+        delimited!(
+            space0,
+            separated_list!(delimited!(space0, char!(','), space0), parse_annotation_field),
+            space0
+        ),
+        return_error!(expect!("a field or `)`", char!(')')))
+    )
+);
+
+named!(
+    parse_annotation_type<&[u8], Annotation, SyntaxError>,
+    do_parse!(
+        name: parse_annotation_name
+            >> fields: opt!(complete!(parse_annotation_fields))
+            >> (Annotation { name, fields: fields.unwrap_or_default() })
+    )
+);
+
+named!(
+    pub parse_annotation_value<&[u8], AnnotationValue, SyntaxError>,
+    alt!(
+        complete!(parse_number) => { AnnotationValue::Number } |
+        complete!(parse_string) => { AnnotationValue::String } |
+        complete!(parse_list) => { AnnotationValue::List } |
+        terminated!(complete!(tag!("true")), not!(parse_annotation_name)) => { |_| AnnotationValue::Bool(true) } |
+        terminated!(complete!(tag!("false")), not!(parse_annotation_name)) => { |_| AnnotationValue::Bool(false) } |
+        parse_annotation_type => { AnnotationValue::Type }
+    )
+);
+
+named!(
+    pub parse_annotation<&[u8], Annotation, SyntaxError>,
+    delimited!(
+        complete!(char!('[')),
+        return_error!(delimited!(space0, expect!("an annotation", parse_annotation_type), space0)),
+        return_error!(expect!("`]`", char!(']')))
+    )
+);
+
+named!(
+    pub parse_annotations<&[u8], Vec<Annotation>, SyntaxError>,
+    many0!(map!(pair!(parse_annotation, space0), |(annotation, _)| annotation))
+);
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_annotation() {
+        assert_eq!(
+            parse_annotation(b"[Deprecated]"),
+            Ok((
+                "".as_bytes(),
+                Annotation {
+                    name: "Deprecated".to_string(),
+                    fields: Vec::new(),
+                }
+            ))
+        );
+        assert_eq!(
+            parse_annotation(
+                b"[improbable.Range( min = -1.5, tags = [\"a\", \"\", true], inner = Inner(x = 5) )]"
+            ),
+            Ok((
+                "".as_bytes(),
+                Annotation {
+                    name: "improbable.Range".to_string(),
+                    fields: vec![
+                        (
+                            "min".to_string(),
+                            AnnotationValue::Number("-1.5".to_string())
+                        ),
+                        (
+                            "tags".to_string(),
+                            AnnotationValue::List(vec![
+                                AnnotationValue::String("a".to_string()),
+                                AnnotationValue::String(String::new()),
+                                AnnotationValue::Bool(true),
+                            ])
+                        ),
+                        (
+                            "inner".to_string(),
+                            AnnotationValue::Type(Annotation {
+                                name: "Inner".to_string(),
+                                fields: vec![(
+                                    "x".to_string(),
+                                    AnnotationValue::Number("5".to_string())
+                                )],
+                            })
+                        ),
+                    ],
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_annotation_error() {
+        assert_eq!(
+            parse_annotation(b"[Range(min 1)]"),
+            Err(nom::Err::Failure(SyntaxError {
+                remaining: 3,
+                kind: nom::error::ErrorKind::Char,
+                expected: vec!["`=`".to_string()],
+                message: None,
+            }))
+        );
+    }
+}
@@ -1,33 +1,46 @@
 use crate::ast::Command;
 use crate::ast::DataType;
+use crate::ast::SourceSpan;
+use crate::parser::comment::space0;
+use crate::parser::comment::space1;
+use crate::parser::error::SyntaxError;
+use nom::call;
 use nom::char;
-use nom::character::complete::multispace0;
-use nom::character::complete::multispace1;
 use nom::delimited;
 use nom::do_parse;
 use nom::named;
+use nom::preceded;
+use nom::return_error;
 use nom::separated_list;
-use nom::tag;
 
 use crate::parser::data_type::parse_type;
-use crate::parser::utils::snake_case as parse_command_name;
+use crate::parser::utils::identifier as parse_command_name;
+use crate::parser::utils::keyword;
+use crate::parser::utils::remaining;
 
 named!(
-    parse_args<Vec<DataType>>,
+    parse_args<&[u8], Vec<DataType>, SyntaxError>,
     delimited!(
         char!('('),
         delimited!(
-            multispace0,
-            separated_list!(delimited!(multispace0, char!(','), multispace0), parse_type),
-            multispace0
+            space0,
+            separated_list!(delimited!(space0, char!(','), space0), expect!("a type", parse_type)),
+            space0
         ),
         char!(')')
     )
 );
 
 named!(
-    pub parse_command<Command>,
+    pub parse_command<&[u8], Command, SyntaxError>,
     do_parse!(
-        tag!("command") >> multispace1 >> r_type: parse_type >> multispace1 >> name: parse_command_name >> multispace0 >> args: parse_args >> (Command { r_type, name, args })
+        start: remaining
+            >> call!(keyword("command"))
+            >> r_type: return_error!(expect!("a command response type", preceded!(space1, expect!("a command response type", parse_type))))
+            >> name: return_error!(expect!("a command name", preceded!(space1, expect!("a command name", parse_command_name))))
+            >> space0
+            >> args: return_error!(parse_args)
+            >> end: remaining
+            >> (Command { name, doc: None, r_type, args, span: Some(SourceSpan { start, end }) })
     )
 );
@@ -0,0 +1,176 @@
+use nom::error::ErrorKind;
+use nom::error::ParseError;
+use nom::Err;
+use nom::IResult;
+
+fn is_space(byte: u8) -> bool {
+    byte == b' ' || byte == b'\t' || byte == b'\r' || byte == b'\n'
+}
+
+fn comment_text(comment: &[u8]) -> Vec<String> {
+    let text = String::from_utf8_lossy(comment);
+    if let Some(line) = text.strip_prefix("//") {
+        let line = line.trim_start_matches('/');
+        vec![line
+            .strip_prefix(' ')
+            .unwrap_or(line)
+            .trim_end()
+            .to_string()]
+    } else {
+        let block = text
+            .trim_start_matches("/*")
+            .trim_start_matches('*')
+            .trim_end_matches("*/");
+        let lines = block
+            .lines()
+            .map(|line| {
+                let line = line.trim();
+                let line = line.strip_prefix('*').unwrap_or(line);
+                line.strip_prefix(' ')
+                    .unwrap_or(line)
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<String>>();
+        let start = lines.iter().position(|l| !l.is_empty()).unwrap_or(0);
+        let end = lines
+            .iter()
+            .rposition(|l| !l.is_empty())
+            .map_or(0, |i| i + 1);
+        lines[start..end.max(start)].to_vec()
+    }
+}
+
+pub fn parse_comment<'a, E: ParseError<&'a [u8]>>(
+    input: &'a [u8],
+) -> IResult<&'a [u8], &'a [u8], E> {
+    if input.starts_with(b"//") {
+        let end = input
+            .iter()
+            .position(|b| *b == b'\n')
+            .unwrap_or(input.len());
+        Ok((&input[end..], &input[..end]))
+    } else if input.starts_with(b"/*") {
+        match input[2..].windows(2).position(|w| w == b"*/") {
+            Some(end) => Ok((&input[end + 4..], &input[..end + 4])),
+            None => Err(Err::Failure(E::add_context(
+                input,
+                "unterminated block comment",
+                E::from_error_kind(input, ErrorKind::TakeUntil),
+            ))),
+        }
+    } else {
+        Err(Err::Error(E::from_error_kind(input, ErrorKind::Tag)))
+    }
+}
+
+/// Skips whitespace and comments, and returns the doc comment they end
+/// with, if any.
+///
+/// A doc comment is the run of comments right above what follows: a blank
+/// line in between, or a comment trailing some code on its line, is not
+/// part of it.
+pub fn parse_doc<'a, E: ParseError<&'a [u8]>>(
+    mut input: &'a [u8],
+) -> IResult<&'a [u8], Option<String>, E> {
+    let mut doc = Vec::new();
+    loop {
+        let spaces = input.iter().take_while(|b| is_space(**b)).count();
+        let newlines = input[..spaces].iter().filter(|b| **b == b'\n').count();
+        input = &input[spaces..];
+        match parse_comment::<E>(input) {
+            Ok((rest, comment)) => {
+                if newlines != 1 {
+                    doc.clear();
+                }
+                if newlines > 0 {
+                    doc.extend(comment_text(comment));
+                }
+                input = rest;
+            }
+            Err(Err::Error(_)) => {
+                if newlines > 1 {
+                    doc.clear();
+                }
+                return Ok((input, Some(doc.join("\n")).filter(|_| !doc.is_empty())));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Like `multispace0`, but comments count as whitespace.
+pub fn space0<'a, E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], (), E> {
+    parse_doc(input).map(|(rest, _)| (rest, ()))
+}
+
+/// Like `multispace1`, but comments count as whitespace.
+pub fn space1<'a, E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], (), E> {
+    let (rest, _) = space0(input)?;
+    if rest.len() == input.len() {
+        Err(Err::Error(E::from_error_kind(input, ErrorKind::MultiSpace)))
+    } else {
+        Ok((rest, ()))
+    }
+}
+
+/// Output of `documented`: the doc comment, then what was parsed.
+pub type Documented<O> = (Option<String>, O);
+
+/// Applies `parser` after any whitespace and comments, and pairs its output
+/// with the doc comment found right above it.
+pub fn documented<'a, O, E, F>(
+    parser: F,
+) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], Documented<O>, E>
+where
+    E: ParseError<&'a [u8]>,
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], O, E>,
+{
+    move |input| {
+        let (input, doc) = parse_doc(input)?;
+        let (input, output) = parser(input)?;
+        Ok((input, (doc, output)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn doc(input: &[u8]) -> (&[u8], Option<String>) {
+        parse_doc::<(&[u8], ErrorKind)>(input).unwrap()
+    }
+
+    #[test]
+    fn test_space0() {
+        assert_eq!(
+            space0::<(&[u8], ErrorKind)>(b" // a\n /* b\n c */ d"),
+            Ok((&b"d"[..], ()))
+        );
+        assert_eq!(
+            space0::<(&[u8], ErrorKind)>(b" /* a"),
+            Err(Err::Failure((&b"/* a"[..], ErrorKind::TakeUntil)))
+        );
+        assert!(space1::<(&[u8], ErrorKind)>(b"a").is_err());
+        assert_eq!(space1::<(&[u8], ErrorKind)>(b"/**/a"), Ok((&b"a"[..], ())));
+    }
+
+    #[test]
+    fn test_parse_doc() {
+        assert_eq!(
+            doc(b"\n// The health.\n// In points.\ntype"),
+            (&b"type"[..], Some("The health.\nIn points.".to_string()))
+        );
+        assert_eq!(
+            doc(b"\n/**\n * The health.\n */\ntype"),
+            (&b"type"[..], Some("The health.".to_string()))
+        );
+        assert_eq!(doc(b"\n// Detached.\n\ntype"), (&b"type"[..], None));
+        assert_eq!(doc(b" // Trailing.\ntype"), (&b"type"[..], None));
+        assert_eq!(
+            doc(b"\n// Header.\n\n// The health.\ntype"),
+            (&b"type"[..], Some("The health.".to_string()))
+        );
+    }
+}
@@ -1,51 +1,77 @@
+use crate::ast::Annotation;
 use crate::ast::Command;
 use crate::ast::Component;
+use crate::ast::ComponentData;
+use crate::ast::Enum;
 use crate::ast::Event;
 use crate::ast::Member;
+use crate::ast::SourceSpan;
+use crate::ast::Type;
+use crate::parser::annotation::parse_annotations;
+use crate::parser::comment::documented;
+use crate::parser::comment::space0;
+use crate::parser::comment::space1;
+use crate::parser::comment::Documented;
+use crate::parser::error::SyntaxError;
+use crate::parser::recovery::parse_body;
+use crate::parser::recovery::statement;
+use crate::parser::recovery::Body;
 use nom::alt;
-use nom::char;
-use nom::character::complete::multispace0;
-use nom::character::complete::multispace1;
-use nom::complete;
-use nom::delimited;
+use nom::call;
 use nom::do_parse;
-use nom::map_res;
 use nom::named;
-use nom::separated_list;
+use nom::preceded;
+use nom::return_error;
 use nom::tag;
-
-use nom::terminated;
-use nom::tuple;
+use nom::IResult;
 
 use crate::parser::command::parse_command;
+use crate::parser::data_type::parse_user_type;
 use crate::parser::event::parse_event;
 use crate::parser::member::parse_member;
-use crate::parser::utils::camel_case as parse_component_name;
+use crate::parser::r#enum::parse_enum;
+use crate::parser::r#type::parse_type;
+use crate::parser::utils::identifier as parse_component_name;
+use crate::parser::utils::keyword;
 use crate::parser::utils::parse_usize;
+use crate::parser::utils::remaining;
 
 enum ComponentProperty {
     ID(usize),
     Member(Member),
     Command(Command),
     Event(Event),
+    Type(Type),
+    Enum(Enum),
+    Data(ComponentData),
 }
 
+type Item = (ComponentProperty, Vec<SyntaxError>);
+
 #[derive(Default)]
 struct ComponentBuilder {
     pub name: Option<String>,
     pub id: Option<usize>,
+    pub annotations: Vec<Annotation>,
     pub members: Vec<Member>,
     pub commands: Vec<Command>,
     pub events: Vec<Event>,
+    pub types: Vec<Type>,
+    pub enums: Vec<Enum>,
+    pub data: Option<ComponentData>,
+    pub span: Option<SourceSpan>,
 }
 
 impl ComponentBuilder {
-    pub fn with_property(mut self, property: ComponentProperty) -> Self {
+    pub fn with_property(mut self, (doc, property): Documented<ComponentProperty>) -> Self {
         match property {
             ComponentProperty::ID(id) => self.id = Some(id),
-            ComponentProperty::Member(m) => self.members.push(m),
-            ComponentProperty::Event(e) => self.events.push(e),
-            ComponentProperty::Command(c) => self.commands.push(c),
+            ComponentProperty::Member(m) => self.members.push(Member { doc, ..m }),
+            ComponentProperty::Event(e) => self.events.push(Event { doc, ..e }),
+            ComponentProperty::Command(c) => self.commands.push(Command { doc, ..c }),
+            ComponentProperty::Type(t) => self.types.push(Type { doc, ..t }),
+            ComponentProperty::Enum(e) => self.enums.push(Enum { doc, ..e }),
+            ComponentProperty::Data(data) => self.data = Some(data),
         };
         self
     }
@@ -55,59 +81,145 @@ impl ComponentBuilder {
         self
     }
 
+    pub fn with_span(mut self, span: SourceSpan) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_annotations(mut self, annotations: Vec<Annotation>) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
     pub fn build(self) -> Result<Component, &'static str> {
         let name = self.name.ok_or("Name could not be found")?;
         let id = self.id.ok_or("ID could not be found")?;
         Ok(Component {
             name,
+            doc: None,
+            annotations: self.annotations,
             id,
             members: self.members,
             commands: self.commands,
             events: self.events,
+            types: self.types,
+            enums: self.enums,
+            data: self.data,
+            span: self.span,
         })
     }
 }
 
 named!(
-    parse_id<usize>,
+    parse_id<&[u8], usize, SyntaxError>,
     do_parse!(
-        tag!("id") >> delimited!(multispace0, tag!("="), multispace0) >> id: parse_usize >> (id)
+        call!(keyword("id"))
+            >> space0
+            >> return_error!(expect!("`=`", tag!("=")))
+            >> space0
+            >> id: return_error!(expect!("a component id", parse_usize))
+            >> (id)
     )
 );
 
 named!(
-    parse_property<ComponentProperty>,
-    alt!(
-        parse_id => { |i| ComponentProperty::ID(i) }  |
-        parse_member => { |m| ComponentProperty::Member(m) } |
-        parse_command => { |c| ComponentProperty::Command(c) } |
-        parse_event => { |e| ComponentProperty::Event(e) }
+    parse_data<&[u8], ComponentData, SyntaxError>,
+    do_parse!(
+        start: remaining
+            >> call!(keyword("data"))
+            >> type_name: return_error!(expect!("a type", preceded!(space1, expect!("a type", parse_user_type))))
+            >> end: remaining
+            >> (ComponentData { type_name, members: Vec::new(), span: Some(SourceSpan { start, end }) })
     )
 );
 
 named!(
-    parse_properties<Vec<ComponentProperty>>,
-    separated_list!(
-        multispace0,
-        terminated!(parse_property, tuple!(multispace0, char!(';')))
+    parse_property<&[u8], ComponentProperty, SyntaxError>,
+    expect!(
+        "`id`, `data`, a field, an event or a command",
+        alt!(
+            parse_id => { ComponentProperty::ID }  |
+            parse_data => { ComponentProperty::Data }  |
+            parse_command => { ComponentProperty::Command } |
+            parse_event => { ComponentProperty::Event } |
+            parse_member => { ComponentProperty::Member }
+        )
     )
 );
 
 named!(
-    parse_component_body<Vec<ComponentProperty>>,
-    delimited!(
-        char!('{'),
-        delimited!(multispace0, parse_properties, multispace0),
-        char!('}')
+    parse_item<&[u8], (ComponentProperty, Vec<SyntaxError>), SyntaxError>,
+    alt!(
+        parse_type => { |(t, errors)| (ComponentProperty::Type(t), errors) } |
+        parse_enum => { |(e, errors)| (ComponentProperty::Enum(e), errors) } |
+        call!(statement(parse_property)) => { |p| (p, Vec::new()) }
     )
 );
 
+fn parse_component_body(input: &[u8]) -> IResult<&[u8], Body<Documented<Item>>, SyntaxError> {
+    parse_body(input, documented(parse_item))
+}
+
+fn build_component(
+    start: usize,
+    span: SourceSpan,
+    annotations: Vec<Annotation>,
+    name: String,
+    (items, mut errors): Body<Documented<Item>>,
+) -> (Component, Vec<SyntaxError>) {
+    let mut builder = items
+        .into_iter()
+        .fold(
+            ComponentBuilder::default(),
+            |acc, (doc, (property, nested_errors))| {
+                errors.extend(nested_errors);
+                if let (ComponentProperty::Data(_), Some(data)) = (&property, &acc.data) {
+                    errors.push(SyntaxError::new(
+                        start,
+                        format!(
+                            "component `{}` already takes its data from `{}`",
+                            name, data.type_name
+                        ),
+                    ));
+                    return acc;
+                }
+                acc.with_property((doc, property))
+            },
+        )
+        .with_name(name)
+        .with_span(span)
+        .with_annotations(annotations);
+    // A malformed statement may well have been the id, which was reported
+    // already.
+    if builder.id.is_none() && errors.is_empty() {
+        errors.push(SyntaxError::new(
+            start,
+            format!(
+                "component `{}` has no `id`",
+                builder.name.clone().unwrap_or_default()
+            ),
+        ));
+    }
+    if builder.id.is_none() {
+        builder.id = Some(0);
+    }
+    (
+        builder.build().expect("component name and id are set"),
+        errors,
+    )
+}
+
 named!(
-    pub parse_component<Component>,
-    map_res!(do_parse!(
-        complete!(tag!("component"))
-            >> name: delimited!(multispace1, parse_component_name, multispace1)
-            >> properties: parse_component_body
-            >> (properties.into_iter().fold(ComponentBuilder::default(), |acc, val| acc.with_property(val)).with_name(name))
-    ), |builder: ComponentBuilder| builder.build())
+    pub parse_component<&[u8], (Component, Vec<SyntaxError>), SyntaxError>,
+    do_parse!(
+        start: call!(remaining)
+            >> annotations: parse_annotations
+            >> header: remaining
+            >> call!(keyword("component"))
+            >> name: return_error!(expect!("a component name", preceded!(space1, expect!("a component name", parse_component_name))))
+            >> end: remaining
+            >> space0
+            >> body: return_error!(parse_component_body)
+            >> (build_component(start, SourceSpan { start: header, end }, annotations, name, body))
+    )
 );
@@ -1,26 +1,28 @@
 use crate::ast::DataType;
+use crate::parser::comment::space0;
+use crate::parser::utils::identifier;
+use crate::parser::utils::identifier_char;
 use nom::alt;
-use nom::bytes::complete::take_while;
-use nom::character::complete::multispace0;
-use nom::character::is_alphabetic;
+use nom::char;
 use nom::complete;
 use nom::delimited;
 use nom::do_parse;
-use nom::map_res;
+use nom::many0;
 use nom::named;
-use nom::one_of;
+use nom::not;
 use nom::pair;
 use nom::separated_pair;
 use nom::tag;
+use nom::terminated;
 
 named!(
     pub parse_one_generic<DataType>,
     delimited!(
         tag!("<"),
         delimited!(
-            multispace0,
-            parse_type_without_generics,
-            multispace0
+            space0,
+            parse_type,
+            space0
         ),
         tag!(">")
     )
@@ -31,17 +33,17 @@ named!(
     delimited!(
         tag!("<"),
         delimited!(
-            multispace0,
+            space0,
             separated_pair!(
-                parse_type_without_generics,
+                parse_type,
                 delimited!(
-                    multispace0,
+                    space0,
                     tag!(","),
-                    multispace0
+                    space0
                 ),
-                parse_type_without_generics
+                parse_type
             ),
-            multispace0
+            space0
         ),
         tag!(">")
     )
@@ -49,7 +51,7 @@ named!(
 
 named!(
     pub parse_primitive<DataType>,
-    alt!(
+    terminated!(alt!(
         complete!(tag!("bool"))     => { |_| DataType::Bool }     |
         complete!(tag!("float"))    => { |_| DataType::Float }    |
         complete!(tag!("bytes"))    => { |_| DataType::Bytes }    |
@@ -67,15 +69,15 @@ named!(
         complete!(tag!("sfixed64")) => { |_| DataType::SFixed64 } |
         complete!(tag!("EntityId")) => { |_| DataType::EntityID } |
         complete!(tag!("Entity"))   => { |_| DataType::Entity }
-    )
+    ), not!(identifier_char))
 );
 
 named!(
     pub parse_user_type<String>,
     do_parse!(
-        first_letter: one_of!("ABCDEFGHIJKLMNOPQRSTUVWXYZ")
-            >> rest: map_res!(complete!(take_while(is_alphabetic)), |s| std::str::from_utf8(s))
-            >> (first_letter.to_string() + rest)
+        package: many0!(complete!(terminated!(identifier, char!('.'))))
+            >> name: identifier
+            >> (package.iter().map(|p| p.clone() + ".").collect::<String>() + &name)
     )
 );
 
@@ -98,15 +100,15 @@ named!(
     parse_type_without_generics<DataType>,
     alt!(
         complete!(parse_primitive) |
-        complete!(parse_user_type) => { |s| DataType::UserDefined(s) }
+        complete!(parse_user_type) => { DataType::UserDefined }
     )
 );
 
 named!(
     pub parse_type<DataType>,
     alt!(
-        parse_type_without_generics |
-        complete!(parse_generic_type)
+        complete!(parse_generic_type) |
+        parse_type_without_generics
     )
 );
 
@@ -186,6 +188,10 @@ mod tests {
             parse_primitive(b"Entity"),
             Ok(("".as_bytes(), DataType::Entity))
         );
+        assert_eq!(
+            parse_primitive(b"EntityAcl"),
+            Err(Error(("Acl".as_bytes(), ErrorKind::Not)))
+        );
         assert_eq!(
             parse_primitive(b"CustomComponent"),
             Err(Error(("CustomComponent".as_bytes(), ErrorKind::Alt)))
@@ -234,8 +240,71 @@ mod tests {
             ))
         );
         assert_eq!(
-            parse_type(b"customComponent"),
-            Err(Error(("customComponent".as_bytes(), ErrorKind::Alt)))
+            parse_type(b"EntityAcl"),
+            Ok((
+                "".as_bytes(),
+                DataType::UserDefined("EntityAcl".to_string())
+            ))
+        );
+        assert_eq!(
+            parse_type(b"custom_component"),
+            Ok((
+                "".as_bytes(),
+                DataType::UserDefined("custom_component".to_string())
+            ))
+        );
+        assert_eq!(
+            parse_type(b"mapping"),
+            Ok(("".as_bytes(), DataType::UserDefined("mapping".to_string())))
+        );
+        assert!(parse_type(b"2d").is_err());
+    }
+
+    #[test]
+    fn test_parse_user_type() {
+        assert_eq!(
+            parse_user_type(b"Coordinates"),
+            Ok(("".as_bytes(), "Coordinates".to_string()))
+        );
+        assert_eq!(
+            parse_user_type(b"improbable.Coordinates>"),
+            Ok((">".as_bytes(), "improbable.Coordinates".to_string()))
+        );
+        assert_eq!(
+            parse_user_type(b"io.nebulis.player.Damage"),
+            Ok(("".as_bytes(), "io.nebulis.player.Damage".to_string()))
+        );
+        assert_eq!(
+            parse_user_type(b"v2.Vector3"),
+            Ok(("".as_bytes(), "v2.Vector3".to_string()))
+        );
+        assert_eq!(
+            parse_user_type(b"Test.io.Damage_Kind"),
+            Ok(("".as_bytes(), "Test.io.Damage_Kind".to_string()))
+        );
+        assert!(parse_user_type(b"io.2d").is_err());
+    }
+
+    #[test]
+    fn test_parse_nested_generics() {
+        assert_eq!(
+            parse_type(b"map<string, list<Damage>>"),
+            Ok((
+                "".as_bytes(),
+                DataType::Map(
+                    Box::new(DataType::String),
+                    Box::new(DataType::List(Box::new(DataType::UserDefined(
+                        "Damage".to_string()
+                    ))))
+                )
+            ))
+        );
+        assert_eq!(
+            parse_type(b"list< option<uint32> >"),
+            Ok((
+                "".as_bytes(),
+                DataType::List(Box::new(DataType::Option(Box::new(DataType::Uint32))))
+            ))
         );
     }
 }
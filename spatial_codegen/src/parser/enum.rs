@@ -1,44 +1,50 @@
 use crate::ast::Enum;
+use crate::ast::SourceSpan;
 use crate::ast::Value;
-use nom::char;
-use nom::character::complete::multispace0;
-use nom::character::complete::multispace1;
-use nom::complete;
-use nom::delimited;
+use crate::parser::annotation::parse_annotations;
+use crate::parser::comment::documented;
+use crate::parser::comment::space0;
+use crate::parser::comment::space1;
+use crate::parser::error::SyntaxError;
+use crate::parser::recovery::parse_body;
+use crate::parser::recovery::statement;
+use crate::parser::recovery::Body;
+use nom::call;
 use nom::do_parse;
 use nom::named;
-use nom::separated_list;
-use nom::tag;
+use nom::preceded;
+use nom::return_error;
+use nom::IResult;
 
-use nom::terminated;
-use nom::tuple;
-
-use crate::parser::utils::camel_case as parse_enum_name;
+use crate::parser::utils::identifier as parse_enum_name;
+use crate::parser::utils::keyword;
+use crate::parser::utils::remaining;
 use crate::parser::value::parse_value;
 
-named!(
-    parse_values<Vec<Value>>,
-    separated_list!(
-        multispace0,
-        terminated!(parse_value, tuple!(multispace0, char!(';')))
-    )
-);
-
-named!(
-    parse_enum_body<Vec<Value>>,
-    delimited!(
-        char!('{'),
-        delimited!(multispace0, parse_values, multispace0),
-        char!('}')
-    )
-);
+fn parse_enum_body(input: &[u8]) -> IResult<&[u8], Body<Value>, SyntaxError> {
+    let (input, (values, errors)) = parse_body(input, documented(statement(parse_value)))?;
+    Ok((
+        input,
+        (
+            values
+                .into_iter()
+                .map(|(doc, value)| Value { doc, ..value })
+                .collect(),
+            errors,
+        ),
+    ))
+}
 
 named!(
-    pub parse_enum<Enum>,
+    pub parse_enum<&[u8], (Enum, Vec<SyntaxError>), SyntaxError>,
     do_parse!(
-        complete!(tag!("enum"))
-            >> name: delimited!(multispace1, parse_enum_name, multispace1)
-            >> values: parse_enum_body
-            >> (Enum { name, values })
+        annotations: parse_annotations
+            >> start: remaining
+            >> call!(keyword("enum"))
+            >> name: return_error!(expect!("an enum name", preceded!(space1, expect!("an enum name", parse_enum_name))))
+            >> end: remaining
+            >> space0
+            >> body: return_error!(parse_enum_body)
+            >> ((Enum { name, doc: None, annotations, values: body.0, span: Some(SourceSpan { start, end }) }, body.1))
     )
 );
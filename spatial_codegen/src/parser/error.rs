@@ -0,0 +1,191 @@
+use crate::diagnostic::Diagnostic;
+use nom::error::ErrorKind;
+use nom::error::ParseError;
+use nom::Err;
+use nom::IResult;
+use std::path::Path;
+
+/// Error produced by the schema parsers.
+///
+/// nom only hands `&[u8]` parsers the input that is left, so the failure is
+/// located by the number of bytes remaining; `offset` turns it back into a
+/// position in the parsed source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub remaining: usize,
+    pub kind: ErrorKind,
+    pub expected: Vec<String>,
+    pub message: Option<String>,
+}
+
+impl SyntaxError {
+    pub fn new<S: ToString>(remaining: usize, message: S) -> Self {
+        Self {
+            remaining,
+            kind: ErrorKind::Verify,
+            expected: Vec::new(),
+            message: Some(message.to_string()),
+        }
+    }
+
+    pub fn from_nom(error: Err<Self>) -> Self {
+        match error {
+            Err::Error(e) | Err::Failure(e) => e,
+            Err::Incomplete(_) => Self::new(0, "unexpected end of file"),
+        }
+    }
+
+    pub fn offset(&self, source: &[u8]) -> usize {
+        source.len().saturating_sub(self.remaining)
+    }
+
+    pub fn to_diagnostic<P: AsRef<Path>>(&self, path: P, source: &str) -> Diagnostic {
+        let offset = self.offset(source.as_bytes());
+        let rest = source.get(offset..).unwrap_or_default();
+        let token_length = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len())
+            .max(rest.chars().next().map(char::len_utf8).unwrap_or(0));
+        let message = self.message.clone().unwrap_or_else(|| {
+            if token_length == 0 {
+                "found end of file".to_string()
+            } else {
+                format!("found `{}`", &rest[..token_length])
+            }
+        });
+        Diagnostic::new(path, message)
+            .with_span(source, offset..offset + token_length)
+            .with_expected(self.expected.clone())
+    }
+
+    pub fn expected<S: ToString>(input: &[u8], expected: S, error: Self) -> Self {
+        if error.message.is_none() && (error.remaining == input.len() || error.expected.is_empty())
+        {
+            Self {
+                remaining: input.len(),
+                kind: error.kind,
+                expected: vec![expected.to_string()],
+                message: None,
+            }
+        } else {
+            error
+        }
+    }
+}
+
+impl<'a> ParseError<&'a [u8]> for SyntaxError {
+    fn from_error_kind(input: &'a [u8], kind: ErrorKind) -> Self {
+        Self {
+            remaining: input.len(),
+            kind,
+            expected: Vec::new(),
+            message: None,
+        }
+    }
+
+    fn append(_: &'a [u8], _: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn add_context(_: &'a [u8], context: &'static str, mut other: Self) -> Self {
+        other.message.get_or_insert_with(|| context.to_string());
+        other
+    }
+
+    fn from_char(input: &'a [u8], c: char) -> Self {
+        Self {
+            remaining: input.len(),
+            kind: ErrorKind::Char,
+            expected: vec![format!("`{}`", c)],
+            message: None,
+        }
+    }
+}
+
+impl<'a> From<(&'a [u8], ErrorKind)> for SyntaxError {
+    fn from((input, kind): (&'a [u8], ErrorKind)) -> Self {
+        Self::from_error_kind(input, kind)
+    }
+}
+
+pub fn expect<'a, O, E: Into<SyntaxError>, S: ToString>(
+    input: &'a [u8],
+    expected: S,
+    result: IResult<&'a [u8], O, E>,
+) -> IResult<&'a [u8], O, SyntaxError> {
+    match result {
+        Ok(o) => Ok(o),
+        Err(Err::Error(e)) => Err(Err::Error(SyntaxError::expected(input, expected, e.into()))),
+        Err(Err::Failure(e)) => Err(Err::Failure(SyntaxError::expected(
+            input,
+            expected,
+            e.into(),
+        ))),
+        Err(Err::Incomplete(needed)) => Err(Err::Incomplete(needed)),
+    }
+}
+
+pub fn expect_syntax<'a, O, S: ToString>(
+    input: &'a [u8],
+    expected: S,
+    result: IResult<&'a [u8], O, SyntaxError>,
+) -> IResult<&'a [u8], O, SyntaxError> {
+    expect(input, expected, result)
+}
+
+/// `expect!("description", parser)` names what `parser` was looking for, so
+/// a failure reads as "expected description" instead of a bare nom error
+/// kind. Named parsers using the default `(&[u8], ErrorKind)` error are
+/// converted on the way; nested macros are expected to produce a
+/// `SyntaxError` already.
+macro_rules! expect (
+    ($i:expr, $expected:expr, $submac:ident!( $($args:tt)* )) => (
+        crate::parser::error::expect_syntax($i, $expected, $submac!($i, $($args)*))
+    );
+    ($i:expr, $expected:expr, $f:expr) => (
+        crate::parser::error::expect($i, $expected, $f($i))
+    );
+);
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use nom::char;
+    use nom::named;
+
+    named!(
+        parse_semicolon<&[u8], char, SyntaxError>,
+        expect!("a semicolon", char!(';'))
+    );
+
+    #[test]
+    fn test_to_diagnostic() {
+        let source = "type A {\n    uint32 a 1;\n}";
+        let error = SyntaxError {
+            remaining: 4,
+            kind: ErrorKind::Tag,
+            expected: vec!["`=`".to_string()],
+            message: None,
+        };
+        assert_eq!(
+            error.to_diagnostic("a.schema", source),
+            Diagnostic::new("a.schema", "found `1`")
+                .with_span(source, 22..23)
+                .with_expected(vec!["`=`".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_expect() {
+        assert_eq!(
+            parse_semicolon(b"}"),
+            Err(Err::Error(SyntaxError {
+                remaining: 1,
+                kind: ErrorKind::Char,
+                expected: vec!["a semicolon".to_string()],
+                message: None,
+            }))
+        );
+    }
+}
@@ -1,15 +1,26 @@
 use crate::ast::Event;
-use nom::character::complete::multispace1;
+use crate::ast::SourceSpan;
+use crate::parser::comment::space1;
+use crate::parser::error::SyntaxError;
+use nom::call;
 use nom::do_parse;
 use nom::named;
-use nom::tag;
+use nom::preceded;
+use nom::return_error;
 
 use crate::parser::data_type::parse_type;
-use crate::parser::utils::snake_case as parse_event_name;
+use crate::parser::utils::identifier as parse_event_name;
+use crate::parser::utils::keyword;
+use crate::parser::utils::remaining;
 
 named!(
-    pub parse_event<Event>,
+    pub parse_event<&[u8], Event, SyntaxError>,
     do_parse!(
-        tag!("event") >> multispace1 >> r_type: parse_type >> multispace1 >> name: parse_event_name >> (Event { r_type, name })
+        start: remaining
+            >> call!(keyword("event"))
+            >> r_type: return_error!(expect!("an event type", preceded!(space1, expect!("an event type", parse_type))))
+            >> name: return_error!(expect!("an event name", preceded!(space1, expect!("an event name", parse_event_name))))
+            >> end: remaining
+            >> (Event { name, doc: None, r_type, span: Some(SourceSpan { start, end }) })
     )
 );
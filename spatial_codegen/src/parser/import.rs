@@ -0,0 +1,53 @@
+use crate::parser::comment::space0;
+use crate::parser::comment::space1;
+use crate::parser::error::SyntaxError;
+use crate::parser::utils::keyword;
+use nom::call;
+use nom::char;
+use nom::delimited;
+use nom::is_not;
+use nom::map_res;
+use nom::named;
+use nom::preceded;
+use nom::return_error;
+
+named!(
+    pub parse_import_path<String>,
+    map_res!(
+        delimited!(char!('"'), is_not!("\"\n"), char!('"')),
+        |s| std::str::from_utf8(s).map(|s| s.to_string())
+    )
+);
+
+named!(
+    pub parse_import<&[u8], String, SyntaxError>,
+    delimited!(
+        call!(keyword("import")),
+        return_error!(expect!("an import path", preceded!(space1, expect!("an import path", parse_import_path)))),
+        return_error!(preceded!(space0, char!(';')))
+    )
+);
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use nom::{error::ErrorKind, Err::Failure};
+
+    #[test]
+    fn test_parse_import() {
+        assert_eq!(
+            parse_import(b"import \"player/health.schema\";"),
+            Ok(("".as_bytes(), "player/health.schema".to_string()))
+        );
+        assert_eq!(
+            parse_import(b"import player/health.schema;"),
+            Err(Failure(SyntaxError {
+                remaining: 21,
+                kind: ErrorKind::Char,
+                expected: vec!["an import path".to_string()],
+                message: None,
+            }))
+        );
+    }
+}
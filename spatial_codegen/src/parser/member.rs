@@ -1,32 +1,41 @@
 use crate::ast::DataType;
 use crate::ast::Member;
+use crate::ast::SourceSpan;
+use crate::parser::annotation::parse_annotations;
+use crate::parser::comment::space0;
+use crate::parser::comment::space1;
+use crate::parser::error::SyntaxError;
 use crate::parser::utils::parse_usize;
-use nom::character::complete::multispace0;
-use nom::character::complete::multispace1;
-use nom::delimited;
+use crate::parser::utils::remaining;
 use nom::do_parse;
 use nom::named;
+use nom::preceded;
+use nom::return_error;
 use nom::tag;
 
 use crate::parser::data_type::parse_type;
-use crate::parser::utils::snake_case as parse_member_name;
+use crate::parser::utils::identifier as parse_member_name;
 
 named!(
-    parse_member_type_name<(DataType, String)>,
+    parse_member_type_name<&[u8], (DataType, String), SyntaxError>,
     do_parse!(
-        member_type: parse_type
-            >> multispace1
-            >> member_name: parse_member_name
+        member_type: expect!("a type", parse_type)
+            >> member_name: return_error!(expect!("a field name", preceded!(space1, expect!("a field name", parse_member_name))))
             >> (member_type, member_name)
     )
 );
 
 named!(
-    pub parse_member<Member>,
+    pub parse_member<&[u8], Member, SyntaxError>,
     do_parse!(
-        type_name: parse_member_type_name
-            >> delimited!(multispace0, tag!("="), multispace0)
-            >> id: parse_usize
-            >> (Member { m_type: type_name.0, name: type_name.1, id })
+        annotations: parse_annotations
+            >> start: remaining
+            >> type_name: parse_member_type_name
+            >> space0
+            >> return_error!(expect!("`=`", tag!("=")))
+            >> space0
+            >> id: return_error!(expect!("a field id", parse_usize))
+            >> end: remaining
+            >> (Member { name: type_name.1, doc: None, annotations, m_type: type_name.0, id, span: Some(SourceSpan { start, end }), boxed: false })
     )
 );
@@ -1,10 +1,16 @@
+#[macro_use]
+pub mod error;
+pub mod annotation;
 pub mod command;
+pub mod comment;
 pub mod component;
 pub mod data_type;
 pub mod r#enum;
 pub mod event;
+pub mod import;
 pub mod member;
 pub mod package_name;
+pub mod recovery;
 pub mod schema_file;
 pub mod r#type;
 pub mod utils;
@@ -1,38 +1,32 @@
+use crate::parser::comment::space0;
+use crate::parser::comment::space1;
+use crate::parser::error::SyntaxError;
+use nom::call;
 use nom::char;
-use nom::character::complete::multispace0;
-use nom::character::complete::multispace1;
-
 use nom::delimited;
-
 use nom::named;
+use nom::preceded;
+use nom::return_error;
 use nom::separated_list;
-use nom::tag;
 
-use nom::tuple;
-
-use crate::parser::utils::snake_case;
+use crate::parser::utils::identifier;
+use crate::parser::utils::keyword;
 use nom::bytes::complete::tag as tag_complete;
 
 named!(
     pub parse_package_components<Vec<String>>,
     separated_list!(
         tag_complete("."),
-        snake_case
+        identifier
     )
 );
 
 named!(
-    pub parse_package_name<Vec<String>>,
+    pub parse_package_name<&[u8], Vec<String>, SyntaxError>,
     delimited!(
-        tuple!(
-            tag!("package"),
-            multispace1
-        ),
-        parse_package_components,
-        tuple!(
-            multispace0,
-            char!(';')
-        )
+        expect!("`package`", call!(keyword("package"))),
+        return_error!(expect!("a package name", preceded!(space1, expect!("a package name", parse_package_components)))),
+        return_error!(preceded!(space0, char!(';')))
     )
 );
 
@@ -0,0 +1,200 @@
+use crate::parser::comment::parse_comment;
+use crate::parser::comment::space0;
+use crate::parser::error::SyntaxError;
+use nom::character::complete::char;
+use nom::error::ParseError;
+use nom::sequence::preceded;
+use nom::sequence::terminated;
+use nom::Err;
+use nom::IResult;
+
+/// Returns the part of `input` at which `error` was raised.
+pub fn error_input<'a>(input: &'a [u8], error: &SyntaxError) -> &'a [u8] {
+    &input[input.len() - error.remaining.min(input.len())..]
+}
+
+/// Returns the length of the comment or string literal `input` starts
+/// with, if any. An unterminated one runs to the end of the input, or of
+/// the line for a string, as the parser reads them.
+fn skipped_len(input: &[u8]) -> Option<usize> {
+    if let Ok((rest, _)) = parse_comment::<SyntaxError>(input) {
+        return Some(input.len() - rest.len());
+    }
+    if input.starts_with(b"/*") {
+        return Some(input.len());
+    }
+    if input.first() != Some(&b'"') {
+        return None;
+    }
+    Some(
+        match input[1..].iter().position(|b| *b == b'"' || *b == b'\n') {
+            Some(end) if input[end + 1] == b'"' => end + 2,
+            Some(end) => end + 1,
+            None => input.len(),
+        },
+    )
+}
+
+/// The bytes of `input` outside comments and string literals, along with
+/// their index, so braces and semicolons in those do not count.
+fn code_bytes(input: &[u8]) -> impl Iterator<Item = (usize, u8)> + '_ {
+    let mut index = 0;
+    std::iter::from_fn(move || {
+        while let Some(len) = skipped_len(&input[index..]) {
+            index += len;
+        }
+        let byte = *input.get(index)?;
+        index += 1;
+        Some((index - 1, byte))
+    })
+}
+
+/// Skips the rest of a malformed statement: up to and including the next
+/// `;`, or up to (but excluding) the `}` closing the enclosing body.
+pub fn skip_statement(input: &[u8]) -> &[u8] {
+    let mut depth = 0usize;
+    for (index, byte) in code_bytes(input) {
+        match byte {
+            b'{' => depth += 1,
+            b'}' if depth == 0 => return &input[index..],
+            b'}' => depth -= 1,
+            b';' if depth == 0 => return &input[index + 1..],
+            _ => {}
+        }
+    }
+    &input[input.len()..]
+}
+
+/// Skips the rest of a malformed definition, up to and including the `}`
+/// closing its body.
+pub fn skip_definition(input: &[u8]) -> &[u8] {
+    let mut depth = 0usize;
+    for (index, byte) in code_bytes(input) {
+        match byte {
+            b'{' => depth += 1,
+            b'}' if depth <= 1 => return &input[index + 1..],
+            b'}' => depth -= 1,
+            _ => {}
+        }
+    }
+    &input[input.len()..]
+}
+
+/// Applies `parser` until it fails without having started anything.
+///
+/// A failure raised once `parser` committed (see `return_error!`) is
+/// recorded, the input is resynchronised with `skip` and parsing carries on,
+/// so a single run reports every malformed definition. `parser` is handed
+/// the input as is, leading comments included, so it can pick up its doc.
+pub fn many_recovering<'a, O, F, S>(
+    mut input: &'a [u8],
+    parser: F,
+    skip: S,
+) -> (&'a [u8], Vec<O>, Vec<SyntaxError>)
+where
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], O, SyntaxError>,
+    S: Fn(&'a [u8]) -> &'a [u8],
+{
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    loop {
+        match parser(input) {
+            Ok((rest, value)) => {
+                values.push(value);
+                input = rest;
+            }
+            Err(Err::Failure(e)) => {
+                input = skip(error_input(input, &e));
+                errors.push(e);
+            }
+            Err(Err::Error(_)) => return (input, values, errors),
+            Err(e) => {
+                errors.push(SyntaxError::from_nom(e));
+                return (&input[input.len()..], values, errors);
+            }
+        }
+    }
+}
+
+/// Output of `parse_body`: the statements parsed, then the errors met.
+pub type Body<O> = (Vec<O>, Vec<SyntaxError>);
+
+/// Applies `parser` to a statement, which ends with a `;`.
+pub fn statement<'a, O, F>(parser: F) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], O, SyntaxError>
+where
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], O, SyntaxError>,
+{
+    terminated(parser, preceded(space0, char(';')))
+}
+
+/// Parses a `{ ... }` body made of statements and nested definitions.
+///
+/// Malformed items are recorded and skipped with `skip_statement` rather
+/// than ending the body, so the items after them are still parsed and
+/// checked. As with `many_recovering`, `item` is expected to skip the
+/// comments in front of it; statements are wrapped in `statement`.
+pub fn parse_body<'a, O, F>(input: &'a [u8], item: F) -> IResult<&'a [u8], Body<O>, SyntaxError>
+where
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], O, SyntaxError>,
+{
+    let (mut input, _) = char('{')(input)?;
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    loop {
+        let next = match space0::<SyntaxError>(input) {
+            Ok((next, _)) => next,
+            Err(e) => {
+                errors.push(SyntaxError::from_nom(e));
+                return Ok((&input[input.len()..], (values, errors)));
+            }
+        };
+        match next.first() {
+            None => {
+                errors.push(SyntaxError::from_char(next, '}'));
+                return Ok((next, (values, errors)));
+            }
+            Some(b'}') => return Ok((&next[1..], (values, errors))),
+            _ => {}
+        }
+        match item(input) {
+            Ok((rest, value)) => {
+                values.push(value);
+                input = rest;
+            }
+            Err(Err::Error(mut e)) | Err(Err::Failure(mut e)) => {
+                if e.remaining == next.len() && !e.expected.is_empty() {
+                    e.expected.push("`}`".to_string());
+                }
+                input = skip_statement(error_input(input, &e));
+                errors.push(e);
+            }
+            Err(e) => {
+                errors.push(SyntaxError::from_nom(e));
+                return Ok((&input[input.len()..], (values, errors)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_skip_statement() {
+        assert_eq!(skip_statement(b"a b; c;"), b" c;");
+        assert_eq!(skip_statement(b"a b }"), b"}");
+        assert_eq!(skip_statement(b"a { b; } c; }"), b" }");
+        assert_eq!(skip_statement(b"a // b; }\n c; }"), b" }");
+        assert_eq!(skip_statement(b"a /* } */ c; }"), b" }");
+        assert_eq!(skip_statement(b"[a(b = \"};\")] c; }"), b" }");
+    }
+
+    #[test]
+    fn test_skip_definition() {
+        assert_eq!(skip_definition(b"A { b; { c; } } d"), b" d");
+        assert_eq!(skip_definition(b"b; } d"), b" d");
+        assert_eq!(skip_definition(b"A { // }\n b; } d"), b" d");
+    }
+}
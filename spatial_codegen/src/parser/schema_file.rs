@@ -2,24 +2,38 @@ use crate::ast::Component;
 use crate::ast::Enum;
 use crate::ast::SchemaFile;
 use crate::ast::Type;
+use crate::parser::comment::documented;
+use crate::parser::comment::space0;
 use crate::parser::component::parse_component;
+use crate::parser::error::SyntaxError;
+use crate::parser::import::parse_import;
 use crate::parser::package_name::parse_package_name;
 use crate::parser::r#enum::parse_enum;
 use crate::parser::r#type::parse_type;
+use crate::parser::recovery::error_input;
+use crate::parser::recovery::many_recovering;
+use crate::parser::recovery::skip_definition;
+use crate::parser::recovery::skip_statement;
 use nom::alt;
-use nom::character::complete::multispace0;
-use nom::delimited;
-use nom::do_parse;
+use nom::error::ErrorKind;
+use nom::error::ParseError;
 use nom::named;
-use nom::separated_list;
+use nom::sequence::preceded;
+use nom::Err;
+use nom::IResult;
+use std::path::PathBuf;
 
 #[derive(Default)]
 pub struct SchemaFileBuilder {
     pub package_name: Option<Vec<String>>,
     pub name: Option<String>,
+    pub path: Option<PathBuf>,
+    pub source: String,
+    pub imports: Vec<String>,
     pub types: Vec<Type>,
     pub enums: Vec<Enum>,
     pub components: Vec<Component>,
+    pub errors: Vec<SyntaxError>,
 }
 
 #[derive(Debug)]
@@ -29,6 +43,16 @@ pub enum SchemaModel {
     Enum(Enum),
 }
 
+impl SchemaModel {
+    pub fn with_doc(self, doc: Option<String>) -> Self {
+        match self {
+            SchemaModel::Type(t) => SchemaModel::Type(Type { doc, ..t }),
+            SchemaModel::Component(c) => SchemaModel::Component(Component { doc, ..c }),
+            SchemaModel::Enum(e) => SchemaModel::Enum(Enum { doc, ..e }),
+        }
+    }
+}
+
 impl SchemaFileBuilder {
     pub fn with_model(mut self, model: SchemaModel) -> Self {
         match model {
@@ -44,6 +68,26 @@ impl SchemaFileBuilder {
         self
     }
 
+    pub fn with_path(mut self, path: PathBuf) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    pub fn with_source(mut self, source: String) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn with_imports(mut self, imports: Vec<String>) -> Self {
+        self.imports = imports;
+        self
+    }
+
+    pub fn with_errors(mut self, errors: Vec<SyntaxError>) -> Self {
+        self.errors.extend(errors);
+        self
+    }
+
     pub fn with_package_name(mut self, package_name: Vec<String>) -> Self {
         self.package_name = Some(package_name);
         self
@@ -52,38 +96,205 @@ impl SchemaFileBuilder {
     pub fn build(self) -> Result<SchemaFile, &'static str> {
         let name = self.name.ok_or("Name could not be found")?;
         let package_name = self.package_name.ok_or("Package Name could not be found")?;
+        let path = self.path.ok_or("Path could not be found")?;
         Ok(SchemaFile {
             package_name,
             name,
+            import_path: path.clone(),
+            path,
+            imports: self.imports,
             components: self.components,
             types: self.types,
             enums: self.enums,
+            source: self.source,
         })
     }
 }
 
 named!(
-    parse_model<SchemaModel>,
+    parse_model<&[u8], (SchemaModel, Vec<SyntaxError>), SyntaxError>,
     alt!(
-        parse_type => { |t| SchemaModel::Type(t) } |
-        parse_component => { |c| SchemaModel::Component(c) } |
-        parse_enum => { |e| SchemaModel::Enum(e) }
+        parse_type => { |(t, errors)| (SchemaModel::Type(t), errors) } |
+        parse_component => { |(c, errors)| (SchemaModel::Component(c), errors) } |
+        parse_enum => { |(e, errors)| (SchemaModel::Enum(e), errors) }
     )
 );
 
-named!(
-    parse_models<Vec<SchemaModel>>,
-    separated_list!(multispace0, parse_model)
-);
+pub fn parse_schema(input: &[u8]) -> IResult<&[u8], SchemaFileBuilder, SyntaxError> {
+    let (input, _) = space0(input)?;
+    let (input, builder) = match parse_package_name(input) {
+        Ok((rest, package_name)) => (
+            rest,
+            SchemaFileBuilder::default().with_package_name(package_name),
+        ),
+        Err(Err::Error(e)) => (input, SchemaFileBuilder::default().with_errors(vec![e])),
+        Err(Err::Failure(e)) => (
+            skip_statement(error_input(input, &e)),
+            SchemaFileBuilder::default().with_errors(vec![e]),
+        ),
+        Err(e) => (
+            &input[input.len()..],
+            SchemaFileBuilder::default().with_errors(vec![SyntaxError::from_nom(e)]),
+        ),
+    };
+    let (input, imports, import_errors) =
+        many_recovering(input, preceded(space0, parse_import), skip_statement);
+    let (input, models, mut model_errors) =
+        many_recovering(input, documented(parse_model), skip_definition);
+    let (input, _) = space0(input)?;
+    // Anything left did not start a definition, and would otherwise be
+    // dropped without a word.
+    if !input.is_empty() {
+        model_errors.push(SyntaxError {
+            expected: vec![
+                "`type`".to_string(),
+                "`enum`".to_string(),
+                "`component`".to_string(),
+            ],
+            ..SyntaxError::from_error_kind(input, ErrorKind::Eof)
+        });
+    }
+    Ok((
+        &input[input.len()..],
+        models
+            .into_iter()
+            .fold(builder, |acc, (doc, (model, errors))| {
+                acc.with_model(model.with_doc(doc)).with_errors(errors)
+            })
+            .with_imports(imports)
+            .with_errors(import_errors)
+            .with_errors(model_errors),
+    ))
+}
 
-named!(
-    pub parse_schema<SchemaFileBuilder>,
-    do_parse!(
-        package_name_parts: parse_package_name
-            >> models: delimited!(multispace0, parse_models, multispace0)
-            >> (models
-                .into_iter()
-                .fold(SchemaFileBuilder::default(), |acc, val| acc.with_model(val))
-                .with_package_name(package_name_parts))
-    )
-);
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_schema_recovers() {
+        let source = b"package io;\n\
+            type A { uint32 a 1; uint32 b = 2; }\n\
+            enum B { X = 1; Y; }\n\
+            type C { uint32 c = 1; }\n";
+        let (rest, builder) = parse_schema(source).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(
+            builder
+                .errors
+                .iter()
+                .map(|e| e.offset(source))
+                .collect::<Vec<usize>>(),
+            vec![30, 66]
+        );
+        assert_eq!(builder.types.len(), 2);
+        assert_eq!(builder.types[0].members.len(), 1);
+        assert_eq!(builder.enums[0].values.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_schema_recovers_past_comments() {
+        let source = b"package io;\n\
+            type A {\n\
+                uint32 a 1 // }\n\
+                ;\n\
+                uint32 b = 2;\n\
+            }\n\
+            type C { uint32 c = 1; }\n";
+        let (rest, builder) = parse_schema(source).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(builder.errors.len(), 1);
+        assert_eq!(builder.types.len(), 2);
+        assert_eq!(builder.types[0].members.len(), 1);
+        assert_eq!(builder.types[0].members[0].name, "b");
+    }
+
+    #[test]
+    fn test_parse_schema_trailing_input() {
+        let source = b"package io;\ntype A {}\ncomponnent B { id = 1; }\n";
+        let (rest, builder) = parse_schema(source).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(builder.types.len(), 1);
+        assert_eq!(
+            builder.errors,
+            vec![SyntaxError {
+                remaining: 25,
+                kind: ErrorKind::Eof,
+                expected: vec![
+                    "`type`".to_string(),
+                    "`enum`".to_string(),
+                    "`component`".to_string(),
+                ],
+                message: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_schema_comments() {
+        let source = b"package io; // The package.\n\
+            // A point.\n\
+            type A {\n\
+            /* Horizontal. */ uint32 x = 1; // Trailing.\n\
+            // Vertical.\n\
+            uint32 /* still a field */ y = 2;\n\
+            }\n";
+        let (rest, builder) = parse_schema(source).unwrap();
+        assert!(rest.is_empty());
+        assert!(builder.errors.is_empty());
+        assert_eq!(builder.types[0].doc, Some("A point.".to_string()));
+        assert_eq!(
+            builder.types[0].members[0].doc,
+            Some("Horizontal.".to_string())
+        );
+        assert_eq!(
+            builder.types[0].members[1].doc,
+            Some("Vertical.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_schema_nested() {
+        let source = b"package io;\n\
+            component Health {\n\
+            id = 1;\n\
+            // The kinds of damage.\n\
+            enum DamageKind { FIRE = 1; }\n\
+            type Hit { type Source {} uint32 points = 1; }\n\
+            DamageKind kind = 1;\n\
+            }\n";
+        let (rest, builder) = parse_schema(source).unwrap();
+        assert!(rest.is_empty());
+        assert!(builder.errors.is_empty());
+        let component = &builder.components[0];
+        assert_eq!(component.members.len(), 1);
+        assert_eq!(
+            component.enums[0].doc,
+            Some("The kinds of damage.".to_string())
+        );
+        assert_eq!(component.types[0].members.len(), 1);
+        assert_eq!(component.types[0].types[0].name, "Source");
+    }
+
+    #[test]
+    fn test_parse_schema_data() {
+        let source = b"package io;\n\
+            component Orbital {\n\
+            id = 1;\n\
+            data Orbit;\n\
+            bool retrograde = 3;\n\
+            data Other;\n\
+            }\n";
+        let (rest, builder) = parse_schema(source).unwrap();
+        assert!(rest.is_empty());
+        let component = &builder.components[0];
+        assert_eq!(component.data.as_ref().unwrap().type_name, "Orbit");
+        assert_eq!(component.members.len(), 1);
+        assert_eq!(builder.errors.len(), 1);
+        assert_eq!(
+            builder.errors[0].message,
+            Some("component `Orbital` already takes its data from `Orbit`".to_string())
+        );
+    }
+}
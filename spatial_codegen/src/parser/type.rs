@@ -1,44 +1,90 @@
+use crate::ast::Annotation;
+use crate::ast::Enum;
 use crate::ast::Member;
+use crate::ast::SourceSpan;
 use crate::ast::Type;
-use nom::char;
-use nom::character::complete::multispace0;
-use nom::character::complete::multispace1;
-use nom::complete;
-use nom::delimited;
+use crate::parser::annotation::parse_annotations;
+use crate::parser::comment::documented;
+use crate::parser::comment::space0;
+use crate::parser::comment::space1;
+use crate::parser::comment::Documented;
+use crate::parser::error::SyntaxError;
+use crate::parser::recovery::parse_body;
+use crate::parser::recovery::statement;
+use crate::parser::recovery::Body;
+use nom::alt;
+use nom::call;
 use nom::do_parse;
 use nom::named;
-use nom::separated_list;
-use nom::tag;
-
-use nom::terminated;
-use nom::tuple;
+use nom::preceded;
+use nom::return_error;
+use nom::IResult;
 
 use crate::parser::member::parse_member;
-use crate::parser::utils::camel_case as parse_type_name;
+use crate::parser::r#enum::parse_enum;
+use crate::parser::utils::identifier as parse_type_name;
+use crate::parser::utils::keyword;
+use crate::parser::utils::remaining;
 
-named!(
-    parse_members<Vec<Member>>,
-    separated_list!(
-        multispace0,
-        terminated!(parse_member, tuple!(multispace0, char!(';')))
-    )
-);
+enum TypeProperty {
+    Member(Member),
+    Type(Type),
+    Enum(Enum),
+}
+
+type Item = (TypeProperty, Vec<SyntaxError>);
 
 named!(
-    parse_type_body<Vec<Member>>,
-    delimited!(
-        char!('{'),
-        delimited!(multispace0, parse_members, multispace0),
-        char!('}')
+    parse_item<&[u8], Item, SyntaxError>,
+    alt!(
+        parse_type => { |(t, errors)| (TypeProperty::Type(t), errors) } |
+        parse_enum => { |(e, errors)| (TypeProperty::Enum(e), errors) } |
+        call!(statement(|i| expect!(i, "a field", parse_member))) => {
+            |m| (TypeProperty::Member(m), Vec::new())
+        }
     )
 );
 
+fn parse_type_body(input: &[u8]) -> IResult<&[u8], Body<Documented<Item>>, SyntaxError> {
+    parse_body(input, documented(parse_item))
+}
+
+fn build_type(
+    span: SourceSpan,
+    annotations: Vec<Annotation>,
+    name: String,
+    (items, mut errors): Body<Documented<Item>>,
+) -> (Type, Vec<SyntaxError>) {
+    let mut definition = Type {
+        name,
+        doc: None,
+        annotations,
+        members: Vec::new(),
+        types: Vec::new(),
+        enums: Vec::new(),
+        span: Some(span),
+    };
+    for (doc, (property, nested_errors)) in items {
+        match property {
+            TypeProperty::Member(m) => definition.members.push(Member { doc, ..m }),
+            TypeProperty::Type(t) => definition.types.push(Type { doc, ..t }),
+            TypeProperty::Enum(e) => definition.enums.push(Enum { doc, ..e }),
+        }
+        errors.extend(nested_errors);
+    }
+    (definition, errors)
+}
+
 named!(
-    pub parse_type<Type>,
+    pub parse_type<&[u8], (Type, Vec<SyntaxError>), SyntaxError>,
     do_parse!(
-        complete!(tag!("type"))
-            >> name: delimited!(multispace1, parse_type_name, multispace1)
-            >> members: parse_type_body
-            >> (Type { name, members })
+        annotations: parse_annotations
+            >> start: remaining
+            >> call!(keyword("type"))
+            >> name: return_error!(expect!("a type name", preceded!(space1, expect!("a type name", parse_type_name))))
+            >> end: remaining
+            >> space0
+            >> body: return_error!(parse_type_body)
+            >> (build_type(SourceSpan { start, end }, annotations, name, body))
     )
 );
@@ -1,61 +1,70 @@
+use nom::alt;
 use nom::character::is_digit;
-use nom::fold_many1;
-use nom::many0;
-use nom::map;
-use nom::map_opt;
+use nom::complete;
+use nom::error::ErrorKind;
+use nom::error::ParseError;
 use nom::map_res;
 use nom::named;
 use nom::one_of;
-use nom::pair;
-use nom::separated_list;
-use nom::tag;
 use nom::take_while1;
+use nom::Err;
+use nom::IResult;
 
 named!(
     pub uppercase<char>,
-    one_of!("ABCDEFGHIJKLMNOPQRSTUVWXYZ")
+    complete!(one_of!("ABCDEFGHIJKLMNOPQRSTUVWXYZ"))
 );
 
 named!(
     pub lowercase<char>,
-    one_of!("abcdefghijklmnopqrstuvwxyz")
+    complete!(one_of!("abcdefghijklmnopqrstuvwxyz"))
 );
 
 named!(
-    pub camel_case_component<String>,
-    map!(pair!(uppercase, many0!(lowercase)), |(c, s)| c.to_string() + &s.iter().collect::<String>())
+    pub digit<char>,
+    complete!(one_of!("0123456789"))
 );
 
 named!(
-    pub camel_case<String>,
-    fold_many1!(camel_case_component, String::new(), |acc, val| acc + &val)
+    pub identifier_char<char>,
+    alt!(uppercase | lowercase | digit | complete!(one_of!("_")))
 );
 
-named!(
-    pub snake_case_component<String>,
-    fold_many1!(lowercase, String::new(), |acc, val| acc + &val.to_string())
-);
+/// Parses a schemalang identifier: ASCII letters, digits and underscores,
+/// not starting with a digit.
+pub fn identifier(input: &[u8]) -> IResult<&[u8], String> {
+    match input.first() {
+        Some(first) if first.is_ascii_alphabetic() || *first == b'_' => {
+            let end = input
+                .iter()
+                .position(|c| !is_identifier_byte(*c))
+                .unwrap_or(input.len());
+            let name = input[..end].iter().map(|c| *c as char).collect();
+            Ok((&input[end..], name))
+        }
+        _ => Err(Err::Error((input, ErrorKind::Alpha))),
+    }
+}
 
-named!(
-    pub upper_snake_case_component<String>,
-    fold_many1!(uppercase, String::new(), |acc, val| acc + &val.to_string())
-);
-
-named!(
-    pub snake_case<String>,
-    map_opt!(map!(separated_list!(tag!("_"), snake_case_component), |v| {
-        let mut it = v.into_iter();
-        it.next().map(|e| it.fold(e, |acc, val| acc + "_" + &val))
-    }), |o| o)
-);
+/// Parses the keyword `word`, unless it only starts a longer identifier.
+pub fn keyword<'a, E: ParseError<&'a [u8]>>(
+    word: &'static str,
+) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], &'a [u8], E> {
+    move |input: &'a [u8]| {
+        let rest = input.get(word.len()..).unwrap_or_default();
+        if input.starts_with(word.as_bytes())
+            && !rest.first().is_some_and(|c| is_identifier_byte(*c))
+        {
+            Ok((rest, &input[..word.len()]))
+        } else {
+            Err(Err::Error(E::from_error_kind(input, ErrorKind::Tag)))
+        }
+    }
+}
 
-named!(
-    pub upper_snake_case<String>,
-    map_opt!(map!(separated_list!(tag!("_"), upper_snake_case_component), |v| {
-        let mut it = v.into_iter();
-        it.next().map(|e| it.fold(e, |acc, val| acc + "_" + &val))
-    }), |o| o)
-);
+fn is_identifier_byte(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
 
 named!(
     pub parse_usize<usize>,
@@ -64,3 +73,51 @@ named!(
         |s: &str| s.parse::<usize>()
     )
 );
+
+pub fn remaining<'a, E: ParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], usize, E> {
+    Ok((input, input.len()))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(
+            identifier(b"Vector3 "),
+            Ok((" ".as_bytes(), "Vector3".to_string()))
+        );
+        assert_eq!(
+            identifier(b"maxHp;"),
+            Ok((";".as_bytes(), "maxHp".to_string()))
+        );
+        assert_eq!(
+            identifier(b"Damage_Kind"),
+            Ok(("".as_bytes(), "Damage_Kind".to_string()))
+        );
+        assert_eq!(
+            identifier(b"_hidden."),
+            Ok((".".as_bytes(), "_hidden".to_string()))
+        );
+        assert_eq!(
+            identifier(b"hp__x"),
+            Ok(("".as_bytes(), "hp__x".to_string()))
+        );
+        assert_eq!(identifier(b"hp_ "), Ok((" ".as_bytes(), "hp_".to_string())));
+        assert!(identifier(b"2hp").is_err());
+        assert!(identifier(b"-hp").is_err());
+    }
+
+    #[test]
+    fn test_keyword() {
+        let parse = |input| keyword::<(&[u8], ErrorKind)>("type")(input);
+        assert_eq!(parse(b"type Hp"), Ok((" Hp".as_bytes(), "type".as_bytes())));
+        assert_eq!(parse(b"type{"), Ok(("{".as_bytes(), "type".as_bytes())));
+        assert_eq!(parse(b"type"), Ok(("".as_bytes(), "type".as_bytes())));
+        assert!(parse(b"types").is_err());
+        assert!(parse(b"type_2").is_err());
+        assert!(parse(b"typ").is_err());
+    }
+}
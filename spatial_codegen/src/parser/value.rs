@@ -1,19 +1,22 @@
 use crate::ast::Value;
+use crate::parser::comment::space0;
+use crate::parser::error::SyntaxError;
 use crate::parser::utils::parse_usize;
-use nom::character::complete::multispace0;
-use nom::delimited;
 use nom::do_parse;
 use nom::named;
+use nom::return_error;
 use nom::tag;
 
-use crate::parser::utils::upper_snake_case as parse_value_name;
+use crate::parser::utils::identifier as parse_value_name;
 
 named!(
-    pub parse_value<Value>,
+    pub parse_value<&[u8], Value, SyntaxError>,
     do_parse!(
-        name: parse_value_name
-            >> delimited!(multispace0, tag!("="), multispace0)
-            >> id: parse_usize
-            >> (Value { name, id })
+        name: expect!("an enum value", parse_value_name)
+            >> space0
+            >> return_error!(expect!("`=`", tag!("=")))
+            >> space0
+            >> id: return_error!(expect!("an enum value id", parse_usize))
+            >> (Value { name, doc: None, id })
     )
 );
@@ -3,10 +3,20 @@ use spatial_macro::spatial_component;
 #[spatial_component(404)]
 struct Mass {
     mass: f64,
+    /// Ids may leave gaps, which take no dirty bits.
+    #[spatial(field = 1000)]
     thrust: f32,
+    /// Sent when the engine is lit, with the thrust it starts at.
+    #[spatial(event = 1)]
+    ignited: f32,
 }
 
 fn main() {
-    let mass = Mass::new(0.2, 0.0);
+    let mut mass = Mass::new(0.2, 0.0);
+    mass.set_thrust(1.5).unwrap();
+    mass.send_ignited(1.5);
     println!("{:?}", mass);
+    for thrust in mass.drain_ignited() {
+        println!("Event {}: ignited at {}", Mass::IGNITED_EVENT_INDEX, thrust);
+    }
 }
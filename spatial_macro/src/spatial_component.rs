@@ -1,8 +1,12 @@
+use crate::utils::generate_constructor;
 use crate::utils::generate_debug_impl;
+use crate::utils::generate_into_fields;
 use crate::utils::get_dirty_bits_count;
+use crate::utils::get_spatial_arg;
+use crate::utils::sort_fields;
+use crate::utils::strip_spatial_attrs;
 use proc_macro::TokenStream;
 use quote::ToTokens;
-use std::convert::identity;
 use syn::parse_macro_input;
 use syn::AttributeArgs;
 use syn::Ident;
@@ -10,27 +14,6 @@ use syn::ItemStruct;
 
 const VALIDATE_INDEX_ERROR: &str = "Unless you are using custom component replication code, this is most likely caused by a code generation bug. Please contact nebulis support if you encounter this issue.";
 
-fn generate_constructor(fields: &syn::FieldsNamed) -> impl ToTokens {
-    let parameters = &fields.named;
-    let body = fields
-        .named
-        .iter()
-        .map(|f| f.ident.as_ref())
-        .filter_map(identity)
-        .fold(quote! {}, |acc, val| quote! { #val, #acc });
-    let bits = (0..get_dirty_bits_count(fields))
-        .map(|_| 0u32)
-        .collect::<Vec<u32>>();
-    quote! {
-        pub fn new(#parameters) -> Self {
-            Self {
-                dirty_bits: [#(#bits),*],
-                #body
-            }
-        }
-    }
-}
-
 fn generate_getter(field: &syn::Field) -> impl ToTokens {
     if let Some(ident) = &field.ident {
         let ty = &field.ty;
@@ -45,15 +28,17 @@ fn generate_getter(field: &syn::Field) -> impl ToTokens {
     }
 }
 
+/// `field_index` is the dirty bit of the field, its position among the
+/// fields sorted by id.
 fn generate_setter(field: &syn::Field, field_index: usize) -> impl ToTokens {
     if let Some(ident) = &field.ident {
         let ty = &field.ty;
         let getter = format_ident!("set_{}", ident);
         quote! {
-            pub fn #getter(&mut self, data: #ty) -> Result<(), &'static str> {
+            pub fn #getter(&mut self, data: #ty) -> ::std::result::Result<(), &'static str> {
                 self.mark_data_dirty(#field_index)?;
                 self.#ident = data;
-                Ok(())
+                ::std::result::Result::Ok(())
             }
         }
     } else {
@@ -62,8 +47,8 @@ fn generate_setter(field: &syn::Field, field_index: usize) -> impl ToTokens {
 }
 
 fn generate_getter_setter(field: &syn::Field, field_index: usize) -> impl ToTokens {
-    let getter = generate_getter(&field);
-    let setter = generate_setter(&field, field_index);
+    let getter = generate_getter(field);
+    let setter = generate_setter(field, field_index);
     quote! {
         #getter
 
@@ -71,14 +56,12 @@ fn generate_getter_setter(field: &syn::Field, field_index: usize) -> impl ToToke
     }
 }
 
-fn generate_is_data_dirty(fields: &syn::FieldsNamed) -> impl ToTokens {
-    let bits = (0..get_dirty_bits_count(fields))
-        .into_iter()
-        .collect::<Vec<usize>>();
+fn generate_is_data_dirty(ids: &[u32]) -> impl ToTokens {
+    let bits = (0..get_dirty_bits_count(ids)).collect::<Vec<usize>>();
     quote! {
         fn is_data_dirty(&self) -> bool {
             let mut data_dirty = false;
-            #(data_dirty |= (self.dirty_bits[#bits] != 0x0);)*
+            #(data_dirty |= (self.__spatial_dirty_bits[#bits] != 0x0);)*
             data_dirty
         }
     }
@@ -86,70 +69,155 @@ fn generate_is_data_dirty(fields: &syn::FieldsNamed) -> impl ToTokens {
 
 fn generate_mark_data_dirty() -> impl ToTokens {
     quote! {
-        fn mark_data_dirty(&mut self, index: usize) -> Result<(), &'static str> {
+        fn mark_data_dirty(&mut self, index: usize) -> ::std::result::Result<(), &'static str> {
             let index = self.validate_index(index)?;
             let dirty_bits_byte_index = index >> 5;
-            self.dirty_bits[dirty_bits_byte_index] |= (0x1 << (index & 31usize)) as u32;
-            Ok(())
+            self.__spatial_dirty_bits[dirty_bits_byte_index] |= (0x1 << (index & 31usize)) as u32;
+            ::std::result::Result::Ok(())
         }
     }
 }
 
-fn generate_validate_index(fields: &syn::FieldsNamed) -> impl ToTokens {
-    let bits_count = ((get_fields_number(fields) as isize) - 1).min(0) as usize;
+fn generate_validate_index(ids: &[u32]) -> impl ToTokens {
+    let bits_count = ids.len().saturating_sub(1);
     let error = format!(
         "\"index\" argument out of range. Valid range is [0, {}]. {}",
         bits_count, VALIDATE_INDEX_ERROR
     );
     quote! {
-        fn validate_index(&self, index: usize) -> Result<usize, &'static str> {
-            if index < 0usize || index > #bits_count {
-                Err(#error)
+        fn validate_index(&self, index: usize) -> ::std::result::Result<usize, &'static str> {
+            if index > #bits_count {
+                ::std::result::Result::Err(#error)
             } else {
-                Ok(index)
+                ::std::result::Result::Ok(index)
             }
         }
     }
 }
 
-fn get_fields_number(fields: &syn::FieldsNamed) -> usize {
-    fields.named.len()
+/// A field marked with `#[spatial(event = index)]`, which is turned into a
+/// queue of the events of its type waiting to be sent.
+struct Event {
+    ident: Ident,
+    ty: syn::Type,
+    index: u32,
+    attrs: Vec<syn::Attribute>,
+}
+
+/// Splits the fields of the component into its data fields, left as they
+/// are, and its events.
+fn split_events(fields: &syn::FieldsNamed) -> (syn::FieldsNamed, Vec<Event>) {
+    let mut data = fields.clone();
+    data.named = syn::punctuated::Punctuated::new();
+    let mut events: Vec<Event> = Vec::new();
+    for field in &fields.named {
+        match get_spatial_arg(field, "event") {
+            Some(index) => {
+                if index == 0 || events.iter().any(|event| event.index == index) {
+                    panic!("Event indices should be unique and start at 1");
+                }
+                let field = strip_spatial_attrs(field);
+                events.push(Event {
+                    ident: field.ident.expect("Component should have named fields"),
+                    ty: field.ty,
+                    index,
+                    attrs: field.attrs,
+                });
+            }
+            None => data.named.push(field.clone()),
+        }
+    }
+    (data, events)
 }
 
-fn generate_base_impl(name: &Ident, fields: &syn::FieldsNamed, id: u32) -> impl ToTokens {
-    let constructor = generate_constructor(fields);
-    let validator = generate_validate_index(fields);
-    let is_data_dirty = generate_is_data_dirty(fields);
+fn generate_event(event: &Event) -> impl ToTokens {
+    let Event {
+        ident,
+        ty,
+        index,
+        attrs,
+    } = event;
+    let name = ident.to_string().trim_start_matches("r#").to_string();
+    let index_const = format_ident!("{}_EVENT_INDEX", name.to_uppercase());
+    let sender = format_ident!("send_{}", name);
+    let drainer = format_ident!("drain_{}", name);
+    let drain_doc = format!(
+        "Takes the `{}` events sent since the last update, oldest first.",
+        name
+    );
+    quote! {
+        pub const #index_const: u32 = #index;
+
+        #(#attrs)*
+        pub fn #sender(&mut self, event: #ty) {
+            self.#ident.push(event);
+        }
+
+        #[doc = #drain_doc]
+        pub fn #drainer(&mut self) -> ::std::vec::Drain<'_, #ty> {
+            self.#ident.drain(..)
+        }
+    }
+}
+
+fn generate_is_dirty(events: &[Event]) -> impl ToTokens {
+    let idents = events.iter().map(|event| &event.ident);
+    quote! {
+        fn has_events(&self) -> bool {
+            false #(|| !self.#idents.is_empty())*
+        }
+
+        /// Whether the component has an update to send, made of its
+        /// changed fields and its pending events.
+        fn is_dirty(&self) -> bool {
+            self.is_data_dirty() || self.has_events()
+        }
+    }
+}
+
+fn generate_base_impl(
+    name: &Ident,
+    fields: &syn::FieldsNamed,
+    ids: &[u32],
+    events: &[Event],
+    id: u32,
+) -> impl ToTokens {
+    let queues = events
+        .iter()
+        .map(|event| &event.ident)
+        .collect::<Vec<&Ident>>();
+    let constructor = generate_constructor(fields, get_dirty_bits_count(ids), &queues);
+    let into_fields = generate_into_fields(fields);
+    let validator = generate_validate_index(ids);
+    let is_data_dirty = generate_is_data_dirty(ids);
     let marker = generate_mark_data_dirty();
+    let is_dirty = generate_is_dirty(events);
+    let events = events.iter().map(generate_event);
     let mut gets_sets = vec![];
-    for (field_index, field) in (&fields.named).iter().enumerate() {
-        gets_sets.push(generate_getter_setter(&field, field_index));
+    for (field_index, field) in fields.named.iter().enumerate() {
+        gets_sets.push(generate_getter_setter(field, field_index));
     }
     quote! {
         impl #name {
             const ID: u32 = #id;
 
             #constructor
+            #into_fields
             #validator
             #is_data_dirty
             #marker
+            #is_dirty
 
             #(#gets_sets)*
+
+            #(#events)*
         }
     }
 }
 
 fn get_id(args: AttributeArgs) -> Option<u32> {
-    if let syn::NestedMeta::Lit(lit) = &args[0] {
-        if let syn::Lit::Int(lit_int) = lit {
-            if let Ok(base10_id) = lit_int.base10_parse::<u32>() {
-                Some(base10_id)
-            } else {
-                None
-            }
-        } else {
-            None
-        }
+    if let syn::NestedMeta::Lit(syn::Lit::Int(lit_int)) = &args[0] {
+        lit_int.base10_parse::<u32>().ok()
     } else {
         None
     }
@@ -160,16 +228,24 @@ pub fn generate_component(attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemStruct);
     let result = if let Some(id) = get_id(args) {
         let struct_name = &input.ident;
+        let attrs = &input.attrs;
 
         if let syn::Fields::Named(fields) = input.fields {
-            let named_fields = &fields.named;
-            let base_impl = generate_base_impl(struct_name, &fields, id);
+            let (fields, events) = split_events(&fields);
+            let (fields, ids) = sort_fields(&fields);
+            let named_fields = fields.named.iter();
+            let queues = events.iter().map(|event| &event.ident);
+            let queue_types = events.iter().map(|event| &event.ty);
+            let base_impl = generate_base_impl(struct_name, &fields, &ids, &events, id);
             let debug_impl = generate_debug_impl(struct_name, &fields);
-            let bits_count = get_dirty_bits_count(&fields);
+            let bits_count = get_dirty_bits_count(&ids);
             quote! {
+                #(#attrs)*
                 pub struct #struct_name {
-                    dirty_bits: [u32; #bits_count],
-                    #named_fields
+                    // Prefixed so that no schema field clashes with it.
+                    __spatial_dirty_bits: [u32; #bits_count],
+                    #(#named_fields,)*
+                    #(#queues: ::std::vec::Vec<#queue_types>,)*
                 }
 
                 #base_impl
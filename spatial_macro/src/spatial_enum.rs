@@ -1,16 +1,143 @@
-use syn::ItemEnum;
+use crate::utils::get_spatial_lit;
+use crate::utils::without_spatial_attrs;
 use proc_macro::TokenStream;
 use syn::parse_macro_input;
+use syn::ItemEnum;
+
+/// A variant, with the name and value it has in the schema.
+struct Value {
+    variant: syn::Variant,
+    name: String,
+    id: u32,
+}
+
+/// Reads the schema name of each variant from `#[spatial(name = "...")]`,
+/// defaulting to the variant name, and its value from its discriminant.
+fn get_values(input: &ItemEnum) -> Vec<Value> {
+    input
+        .variants
+        .iter()
+        .map(|variant| {
+            let name = match get_spatial_lit(&variant.attrs, "name") {
+                Some(syn::Lit::Str(name)) => name.value(),
+                Some(_) => panic!("Expected a string for name"),
+                None => variant.ident.to_string(),
+            };
+            let id = match &variant.discriminant {
+                Some((
+                    _,
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Int(lit_int),
+                        ..
+                    }),
+                )) => lit_int.base10_parse::<u32>().expect("Expected a u32 value"),
+                _ => panic!("Enum values should have an integer discriminant"),
+            };
+            let variant = syn::Variant {
+                attrs: without_spatial_attrs(&variant.attrs),
+                ..variant.clone()
+            };
+            Value { variant, name, id }
+        })
+        .collect()
+}
 
 pub fn generate_enum(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemEnum);
 
     let enum_name = &input.ident;
-    let variants = &input.variants;
+    let name_str = enum_name.to_string();
+    let attrs = &input.attrs;
+    let values = get_values(&input);
+    let variants = values.iter().map(|value| &value.variant);
+    let idents = values
+        .iter()
+        .map(|value| &value.variant.ident)
+        .collect::<Vec<&syn::Ident>>();
+    let names = values
+        .iter()
+        .map(|value| value.name.as_str())
+        .collect::<Vec<&str>>();
+    let ids = values.iter().map(|value| value.id).collect::<Vec<u32>>();
+    // A representation cannot be given to an enum without variants.
+    let repr = if values.is_empty() {
+        quote! {}
+    } else {
+        quote! { #[repr(u32)] }
+    };
+    let default = values.iter().find(|value| value.id == 0).map(|value| {
+        let ident = &value.variant.ident;
+        quote! {
+            impl ::std::default::Default for #enum_name {
+                fn default() -> Self {
+                    Self::#ident
+                }
+            }
+        }
+    });
 
     let result = quote! {
+        #(#attrs)*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #repr
         pub enum #enum_name {
-            #variants
+            #(#variants,)*
+        }
+
+        impl #enum_name {
+            /// Every value of the enum, in declaration order.
+            pub const ALL: &'static [Self] = &[#(Self::#idents),*];
+
+            /// The name of the value in the schema.
+            pub fn schema_name(&self) -> &'static str {
+                match *self {
+                    #(Self::#idents => #names,)*
+                }
+            }
+        }
+
+        #default
+
+        impl ::std::convert::From<#enum_name> for u32 {
+            fn from(value: #enum_name) -> Self {
+                value as u32
+            }
+        }
+
+        impl ::std::convert::TryFrom<u32> for #enum_name {
+            type Error = ::std::string::String;
+
+            fn try_from(value: u32) -> ::std::result::Result<Self, Self::Error> {
+                match value {
+                    #(#ids => ::std::result::Result::Ok(Self::#idents),)*
+                    _ => ::std::result::Result::Err(::std::format!(
+                        "{} is not a value of {}",
+                        value,
+                        #name_str
+                    )),
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for #enum_name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.write_str(self.schema_name())
+            }
+        }
+
+        impl ::std::str::FromStr for #enum_name {
+            type Err = ::std::string::String;
+
+            fn from_str(name: &str) -> ::std::result::Result<Self, Self::Err> {
+                match name {
+                    #(#names => ::std::result::Result::Ok(Self::#idents),)*
+                    _ => ::std::result::Result::Err(::std::format!(
+                        "`{}` is not a value of {}",
+                        name,
+                        #name_str
+                    )),
+                }
+            }
         }
     };
     result.into()
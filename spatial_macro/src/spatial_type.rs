@@ -1,23 +1,37 @@
+use crate::utils::generate_constructor;
+use crate::utils::generate_debug_impl;
+use crate::utils::generate_into_fields;
 use crate::utils::get_dirty_bits_count;
+use crate::utils::sort_fields;
 use proc_macro::TokenStream;
 use syn::parse_macro_input;
 use syn::ItemStruct;
-use crate::utils::generate_debug_impl;
 
 pub fn generate_type(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemStruct);
     let struct_name = &input.ident;
+    let attrs = &input.attrs;
 
     let result = if let syn::Fields::Named(fields) = input.fields {
+        let (fields, ids) = sort_fields(&fields);
         let named_fields = &fields.named;
         let debug_impl = generate_debug_impl(struct_name, &fields);
-        let bits_count = get_dirty_bits_count(&fields);
+        let bits_count = get_dirty_bits_count(&ids);
+        let constructor = generate_constructor(&fields, bits_count, &[]);
+        let into_fields = generate_into_fields(&fields);
         quote! {
+            #(#attrs)*
             pub struct #struct_name {
-                dirty_bits: [u32; #bits_count],
+                // Prefixed so that no schema field clashes with it.
+                __spatial_dirty_bits: [u32; #bits_count],
                 #named_fields
             }
 
+            impl #struct_name {
+                #constructor
+                #into_fields
+            }
+
             #debug_impl
 
         }
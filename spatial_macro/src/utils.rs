@@ -1,5 +1,4 @@
 use quote::ToTokens;
-use std::convert::identity;
 use syn::Ident;
 
 pub fn generate_debug_impl(name: &Ident, fields: &syn::FieldsNamed) -> impl ToTokens {
@@ -7,16 +6,15 @@ pub fn generate_debug_impl(name: &Ident, fields: &syn::FieldsNamed) -> impl ToTo
     let fields_name = fields
         .named
         .iter()
-        .map(|f| f.ident.as_ref())
-        .filter_map(identity)
+        .filter_map(|f| f.ident.as_ref())
         .collect::<Vec<&Ident>>();
     let fields_name_str = fields_name
         .iter()
-        .map(|f| f.to_string())
+        .map(|f| f.to_string().trim_start_matches("r#").to_string())
         .collect::<Vec<String>>();
     quote! {
-        impl std::fmt::Debug for #name {
-            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        impl ::std::fmt::Debug for #name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
                 f.debug_struct(#name_str)
                     #(.field(#fields_name_str, &self.#fields_name))*
                     .finish()
@@ -25,6 +23,130 @@ pub fn generate_debug_impl(name: &Ident, fields: &syn::FieldsNamed) -> impl ToTo
     }
 }
 
-pub fn get_dirty_bits_count(fields: &syn::FieldsNamed) -> usize {
-    (fields.named.len() / 32) + 1
-}
\ No newline at end of file
+/// Returns the value of `key` in the `#[spatial(...)]` attributes of
+/// `field`, as in `#[spatial(event = 1)]`.
+pub fn get_spatial_arg(field: &syn::Field, key: &str) -> Option<u32> {
+    match get_spatial_lit(&field.attrs, key)? {
+        syn::Lit::Int(lit_int) => Some(lit_int.base10_parse::<u32>().unwrap_or_else(|_| {
+            panic!(
+                "Expected an integer from 0 to {} for {}, found {}",
+                u32::MAX,
+                key,
+                lit_int
+            )
+        })),
+        _ => panic!("Expected an integer for {}", key),
+    }
+}
+
+/// Returns the literal given to `key` in the `#[spatial(...)]` attributes
+/// among `attrs`.
+pub fn get_spatial_lit(attrs: &[syn::Attribute], key: &str) -> Option<syn::Lit> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("spatial"))
+        .flat_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => list.nested,
+            _ => panic!("Expected #[spatial(key = value, ...)]"),
+        })
+        .find_map(|nested| match nested {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(pair)) if pair.path.is_ident(key) => {
+                Some(pair.lit)
+            }
+            _ => None,
+        })
+}
+
+/// Removes the `#[spatial(...)]` attributes of `field`, which are meant
+/// for the macros only.
+pub fn strip_spatial_attrs(field: &syn::Field) -> syn::Field {
+    syn::Field {
+        attrs: without_spatial_attrs(&field.attrs),
+        ..field.clone()
+    }
+}
+
+pub fn without_spatial_attrs(attrs: &[syn::Attribute]) -> Vec<syn::Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| !attr.path.is_ident("spatial"))
+        .cloned()
+        .collect()
+}
+
+/// Sorts the fields by their schema id, given by `#[spatial(field = id)]`
+/// or else by their position, starting at 1, and returns them along with
+/// their ids. Ordering the fields by id rather than by declaration keeps
+/// the generated code stable when a schema reorders its fields.
+pub fn sort_fields(fields: &syn::FieldsNamed) -> (syn::FieldsNamed, Vec<u32>) {
+    let mut indexed = fields
+        .named
+        .iter()
+        .enumerate()
+        .map(|(position, field)| {
+            let id = get_spatial_arg(field, "field").unwrap_or(position as u32 + 1);
+            (id, strip_spatial_attrs(field))
+        })
+        .collect::<Vec<(u32, syn::Field)>>();
+    indexed.sort_by_key(|(id, _)| *id);
+    if indexed.first().map(|(id, _)| *id) == Some(0)
+        || indexed.windows(2).any(|pair| pair[0].0 == pair[1].0)
+    {
+        panic!("Field ids should be unique and start at 1");
+    }
+    let mut sorted = fields.clone();
+    sorted.named = indexed.iter().map(|(_, field)| field.clone()).collect();
+    (sorted, indexed.into_iter().map(|(id, _)| id).collect())
+}
+
+/// `queues` are the fields holding pending events, which start empty.
+pub fn generate_constructor(
+    fields: &syn::FieldsNamed,
+    bits_count: usize,
+    queues: &[&Ident],
+) -> impl ToTokens {
+    // Field attributes, docs included, are not allowed on parameters.
+    let parameters = fields.named.iter().map(|f| {
+        let ident = &f.ident;
+        let ty = &f.ty;
+        quote! { #ident: #ty }
+    });
+    let body = fields
+        .named
+        .iter()
+        .filter_map(|f| f.ident.as_ref())
+        .fold(quote! {}, |acc, val| quote! { #val, #acc });
+    let bits = (0..bits_count).map(|_| 0u32).collect::<Vec<u32>>();
+    quote! {
+        #[allow(clippy::too_many_arguments)]
+        pub fn new(#(#parameters),*) -> Self {
+            Self {
+                __spatial_dirty_bits: [#(#bits),*],
+                #body
+                #(#queues: ::std::vec::Vec::new(),)*
+            }
+        }
+    }
+}
+
+pub fn generate_into_fields(fields: &syn::FieldsNamed) -> impl ToTokens {
+    let idents = fields
+        .named
+        .iter()
+        .filter_map(|f| f.ident.as_ref())
+        .collect::<Vec<&Ident>>();
+    let types = fields.named.iter().map(|f| &f.ty);
+    quote! {
+        #[allow(clippy::unused_unit)]
+        pub fn into_fields(self) -> (#(#types,)*) {
+            (#(self.#idents,)*)
+        }
+    }
+}
+
+/// The number of `u32` needed to hold a dirty bit for each of `ids`, the
+/// bit of a field being its position among the fields sorted by id, so
+/// sparse ids take no more bits than there are fields.
+pub fn get_dirty_bits_count(ids: &[u32]) -> usize {
+    ids.len().saturating_sub(1) / 32 + 1
+}
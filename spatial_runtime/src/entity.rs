@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The id of a component, as given in its schema.
+pub type ComponentId = u32;
+
+/// The id of an entity, the schema's `EntityId`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct EntityId(pub i64);
+
+impl EntityId {
+    pub fn new(id: i64) -> Self {
+        Self(id)
+    }
+
+    pub fn id(self) -> i64 {
+        self.0
+    }
+
+    /// Whether the id can belong to an entity, as ids start at 1.
+    pub fn is_valid(self) -> bool {
+        self.0 > 0
+    }
+}
+
+impl From<i64> for EntityId {
+    fn from(id: i64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<EntityId> for i64 {
+    fn from(id: EntityId) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Display for EntityId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// An entity as a value, the schema's `Entity`: the data of each of its
+/// components, serialized, by component id.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Entity {
+    components: BTreeMap<ComponentId, Vec<u8>>,
+}
+
+impl Entity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the data of component `id`, returning its previous data.
+    pub fn insert(&mut self, id: ComponentId, data: Vec<u8>) -> Option<Vec<u8>> {
+        self.components.insert(id, data)
+    }
+
+    pub fn remove(&mut self, id: ComponentId) -> Option<Vec<u8>> {
+        self.components.remove(&id)
+    }
+
+    pub fn get(&self, id: ComponentId) -> Option<&[u8]> {
+        self.components.get(&id).map(Vec::as_slice)
+    }
+
+    pub fn contains(&self, id: ComponentId) -> bool {
+        self.components.contains_key(&id)
+    }
+
+    /// The ids of the components of the entity, in increasing order.
+    pub fn component_ids(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        self.components.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_entity_id() {
+        let mut ids = vec![EntityId(3), EntityId::new(1), EntityId::from(2)];
+        ids.sort();
+        assert_eq!(ids, vec![EntityId(1), EntityId(2), EntityId(3)]);
+        assert_eq!(EntityId(42).to_string(), "42");
+        assert!(!EntityId::default().is_valid());
+    }
+
+    #[test]
+    fn test_entity() {
+        let mut entity = Entity::new();
+        assert_eq!(entity.insert(601, vec![1]), None);
+        assert_eq!(entity.insert(54, vec![2]), None);
+        assert_eq!(entity.insert(601, vec![3]), Some(vec![1]));
+        assert_eq!(entity.get(601), Some(&[3u8][..]));
+        assert_eq!(entity.component_ids().collect::<Vec<_>>(), vec![54, 601]);
+        assert_eq!(entity.remove(54), Some(vec![2]));
+        assert!(!entity.contains(54));
+        assert_eq!(entity.len(), 1);
+    }
+}
@@ -0,0 +1,15 @@
+//! The types generated code relies on for the schema primitives which have
+//! no Rust counterpart.
+
+mod entity;
+mod primitives;
+
+pub use entity::ComponentId;
+pub use entity::Entity;
+pub use entity::EntityId;
+pub use primitives::Fixed32;
+pub use primitives::Fixed64;
+pub use primitives::SFixed32;
+pub use primitives::SFixed64;
+pub use primitives::SInt32;
+pub use primitives::SInt64;
@@ -0,0 +1,83 @@
+use std::fmt;
+
+/// Defines a wrapper around `$inner` for the schema primitive `$schema`,
+/// which is encoded differently from the plain integer.
+macro_rules! primitive {
+    ($(#[$doc:meta])* $name:ident($inner:ty), $schema:expr) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+        pub struct $name(pub $inner);
+
+        impl $name {
+            /// The name of the type in a schema.
+            pub const SCHEMA_TYPE: &'static str = $schema;
+
+            pub fn get(self) -> $inner {
+                self.0
+            }
+        }
+
+        impl From<$inner> for $name {
+            fn from(value: $inner) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for $inner {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+primitive!(
+    /// A signed integer, zigzag-encoded so small negative values stay short.
+    SInt32(i32),
+    "sint32"
+);
+primitive!(
+    /// A signed integer, zigzag-encoded so small negative values stay short.
+    SInt64(i64),
+    "sint64"
+);
+primitive!(
+    /// An unsigned integer, always encoded on 4 bytes.
+    Fixed32(u32),
+    "fixed32"
+);
+primitive!(
+    /// An unsigned integer, always encoded on 8 bytes.
+    Fixed64(u64),
+    "fixed64"
+);
+primitive!(
+    /// A signed integer, always encoded on 4 bytes.
+    SFixed32(i32),
+    "sfixed32"
+);
+primitive!(
+    /// A signed integer, always encoded on 8 bytes.
+    SFixed64(i64),
+    "sfixed64"
+);
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(SInt32::from(-3).get(), -3);
+        assert_eq!(i64::from(SFixed64(7)), 7);
+        assert_eq!(Fixed32(12).to_string(), "12");
+        assert_eq!(Fixed64::SCHEMA_TYPE, "fixed64");
+    }
+}
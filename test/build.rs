@@ -1,6 +1,36 @@
+use spatial_codegen::Builder;
+use spatial_codegen::ModuleMap;
 use spatial_codegen::AST;
+use std::path::PathBuf;
 
 fn main() {
-    let schema = AST::from("../schema");
-    schema.generate("./src/generated").expect("Can't produce schema");
+    Builder::new()
+        .with_schema_root("../schema")
+        .generate()
+        .unwrap_or_else(|error| panic!("\n{}", error));
+    // The same modules as one file, which must expose the same paths.
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
+    Builder::new()
+        .with_schema_root("../schema")
+        .with_out_dir(out_dir.join("single_file"))
+        .with_single_file(true)
+        .generate()
+        .unwrap_or_else(|error| panic!("\n{}", error));
+    // The standard library alone, as a shared crate would generate it, and
+    // the schemas under other paths, referring to it.
+    AST::default()
+        .generate_file(out_dir.join("standard_library.rs"))
+        .unwrap();
+    Builder::new()
+        .with_schema_root("../schema")
+        .with_out_dir(out_dir.join("mapped"))
+        .with_single_file(true)
+        .with_modules(
+            ModuleMap::new()
+                .with_prefix("io.nebulis", "game")
+                .with_root("crate::mapped")
+                .with_standard_library("crate::standard_library"),
+        )
+        .generate()
+        .unwrap_or_else(|error| panic!("\n{}", error));
 }
@@ -1,6 +1,47 @@
-mod generated;
+#[allow(dead_code, unused_imports)]
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/spatial_schema.rs"));
+}
+
+#[allow(dead_code, unused_imports)]
+mod single_file {
+    include!(concat!(env!("OUT_DIR"), "/single_file/spatial_schema.rs"));
+}
+
+#[allow(dead_code, unused_imports)]
+mod standard_library {
+    include!(concat!(env!("OUT_DIR"), "/standard_library.rs"));
+}
+
+#[allow(dead_code, unused_imports)]
+mod mapped {
+    include!(concat!(env!("OUT_DIR"), "/mapped/spatial_schema.rs"));
+}
+
+use generated::io::nebulis::player::*;
+use std::convert::TryFrom;
+
+struct Player;
+
+impl HealthCommandHandler for Player {
+    fn damage(&mut self, _request: Damage) -> DamageResponse {
+        DamageResponse::new()
+    }
+}
 
 fn main() {
     let mass = generated::io::nebulis::GravityPoint::new();
     println!("Hello World: {:?} ", mass);
+    let mass = single_file::io::nebulis::GravityPoint::new();
+    println!("From a single file: {:?} ", mass);
+    let mass = mapped::game::GravityPoint::new();
+    println!("From other modules: {:?} ", mass);
+    let response = Player.handle_request(Health::damage_request(Damage::new(10)));
+    println!(
+        "Command {}: {:?}",
+        response.command_index(),
+        response.into_damage()
+    );
+    let state = LifeState::try_from(2).unwrap_or_default();
+    println!("{} = {}", state, u32::from(state));
 }